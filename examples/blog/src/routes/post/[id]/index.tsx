@@ -17,6 +17,10 @@ export default component$(() => {
       <ul aria-labelledby="tags">
         {page.tags.map(tag => <a href={`/tags/${tag}`}>{tag}</a>)}
       </ul>
+      <footer>
+        {page._prev && <a href={page._prev.url}>Previous: {page._prev.title}</a>}
+        {page._next && <a href={page._next.url}>Next: {page._next.title}</a>}
+      </footer>
     </article>
   }}
   />
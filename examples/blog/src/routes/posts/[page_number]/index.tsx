@@ -1,15 +1,22 @@
 import { component$, Resource } from "@builder.io/qwik";
 import { RequestHandler, useEndpoint } from "@builder.io/qwik-city";
 import Testimonial from "../../../components/testimonial"
+import { pages } from "../../../content-generated/pages/posts";
 import { RouteParams } from "./generated";
 
 export default component$(() => {
   const content = useEndpoint<typeof onGet>();
   return <Resource value={content}
-    onResolved={({ post, testimonial }) => {
+    onResolved={({ posts, prev, next, testimonial }) => {
       return <>
         <article>
-
+          <ul>
+            {posts.map((post) => <li key={post._id}>
+              <a href={`/post/${post._id}`}>{post.title}</a>
+            </li>)}
+          </ul>
+          {prev && <a href={`/posts/${prev}`}>Newer posts</a>}
+          {next && <a href={`/posts/${next}`}>Older posts</a>}
         </article>
         {testimonial && <Testimonial testimonial={testimonial} />}
       </>
@@ -18,12 +25,22 @@ export default component$(() => {
   />
 })
 
+type Page = Awaited<ReturnType<(typeof pages)[keyof typeof pages]>>;
+
 export type Content = {
-  post: any
+  posts: Page["items"]
+  prev: number | undefined
+  next: number | undefined
   testimonial: any | undefined
 }
 
-export const onGet: RequestHandler<Content> = ({ params }) => {
-  let { id } = params as RouteParams;
-  return { post: undefined, testimonial: undefined }
-}
\ No newline at end of file
+export const onGet: RequestHandler<Content> = async ({ params, response }) => {
+  const { page_number } = params as RouteParams;
+  // Only the requested page of posts is imported.
+  const load = pages[page_number as keyof typeof pages];
+  if (!load) {
+    throw response.error(404);
+  }
+  const { items, prev, next } = await load();
+  return { posts: items, prev, next, testimonial: undefined }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashMap,
+    hash::Hasher,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    diagnostics::Report,
+    navigation::{Link, Neighbours},
+    types::{Content, Token},
+    utils::StableHasher,
+};
+
+/// Name of the manifest written to the output directory.
+pub const MANIFEST_FILE: &str = ".content-manifest";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub hash: u64,
+    /// Generated module, relative to the output directory.
+    pub output: String,
+}
+
+/// What a previous build produced, keyed by source path relative to the input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub config: u64,
+    /// Paths and frontmatter of every file, all that the collections,
+    /// taxonomies, schemas and pages modules are made from.
+    pub groups: u64,
+    pub files: HashMap<String, Entry>,
+}
+
+impl Manifest {
+    /// Builds the manifest describing `content` as it is now, with the
+    /// `neighbours` of its pages.
+    pub fn from_content(config: &Config, content: &Content, neighbours: &[Neighbours]) -> Self {
+        let input = config.input.to_string_lossy();
+        let files_dir = Path::new(&config.naming.files);
+        let mut groups = StableHasher::default();
+        let mut files = HashMap::with_capacity(content.len());
+        for (token, neighbours) in content.tokens().iter().zip(neighbours) {
+            let path = content.path(token);
+            let relative = path
+                .strip_prefix(&*input)
+                .unwrap_or(path)
+                .trim_start_matches('/');
+            // Both modules import every file and type its frontmatter.
+            groups.write(relative.as_bytes());
+            groups.write(content.frontmatter_raw(token).as_bytes());
+            let output = crate::utils::output_path(files_dir, relative);
+            // Modules also link to their neighbours, which other edits move.
+            let mut hash = StableHasher::default();
+            hash.write(content.source(token).as_bytes());
+            for idx in [neighbours.prev, neighbours.next] {
+                let mut link = Vec::new();
+                if let Some(idx) = idx {
+                    let _ = Link::new(content, config, idx).write_js(&mut link);
+                }
+                hash.write(&link);
+                hash.write_u8(0);
+            }
+            files.insert(
+                relative.to_owned(),
+                Entry {
+                    hash: hash.finish(),
+                    output: output.to_string_lossy().into_owned(),
+                },
+            );
+        }
+        Self {
+            config: config.fingerprint(),
+            groups: groups.finish(),
+            files,
+        }
+    }
+    pub fn load(path: &Path) -> Option<Self> {
+        Self::parse(&std::fs::read_to_string(path).ok()?)
+    }
+    fn parse(src: &str) -> Option<Self> {
+        let mut lines = src.lines();
+        let mut header = lines.next()?.split(' ');
+        if header.next()? != "qwik-city-content" || header.next()? != env!("CARGO_PKG_VERSION") {
+            return None;
+        }
+        let config = u64::from_str_radix(header.next()?, 16).ok()?;
+        let mut hash_line = |name: &str| {
+            let (key, hash) = lines.next()?.split_once(' ')?;
+            if key != name {
+                return None;
+            }
+            u64::from_str_radix(hash, 16).ok()
+        };
+        let groups = hash_line("groups")?;
+        let mut files = HashMap::new();
+        for line in lines {
+            let mut parts = line.split('\t');
+            let hash = u64::from_str_radix(parts.next()?, 16).ok()?;
+            let source = parts.next()?;
+            let output = parts.next()?;
+            files.insert(
+                source.to_owned(),
+                Entry {
+                    hash,
+                    output: output.to_owned(),
+                },
+            );
+        }
+        Some(Self {
+            config,
+            groups,
+            files,
+        })
+    }
+    pub fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_fmt(format_args!(
+            "qwik-city-content {} {:x}\n",
+            env!("CARGO_PKG_VERSION"),
+            self.config
+        ))?;
+        w.write_fmt(format_args!("groups {:x}\n", self.groups))?;
+        let mut sources: Vec<&String> = self.files.keys().collect();
+        sources.sort();
+        for source in sources {
+            let entry = &self.files[source];
+            w.write_fmt(format_args!(
+                "{:x}\t{}\t{}\n",
+                entry.hash, source, entry.output
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the previous build's manifest with the current content so jobs
+/// can skip work whose inputs did not change.
+#[derive(Debug)]
+pub struct Cache {
+    output: PathBuf,
+    input: String,
+    previous: Option<Manifest>,
+    current: Manifest,
+    neighbours: Vec<Neighbours>,
+}
+
+impl Cache {
+    /// Loads the manifest from `config.output`. With `force`, or when the
+    /// manifest was written with different settings, nothing counts as fresh.
+    pub fn new(config: &Config, content: &Content, force: bool) -> Self {
+        let neighbours = crate::navigation::neighbours(content, config);
+        let current = Manifest::from_content(config, content, &neighbours);
+        let previous = if force {
+            None
+        } else {
+            Manifest::load(&config.output.join(MANIFEST_FILE))
+                .filter(|m| m.config == current.config)
+        };
+        Self {
+            output: config.output.clone(),
+            input: config.input.to_string_lossy().into_owned(),
+            previous,
+            current,
+            neighbours,
+        }
+    }
+    /// The same cache with nothing counting as fresh.
+    pub fn forced(&self) -> Self {
+        Self {
+            output: self.output.clone(),
+            input: self.input.clone(),
+            previous: None,
+            current: self.current.clone(),
+            neighbours: self.neighbours.clone(),
+        }
+    }
+    /// The neighbours of every page, indexed like `Content::tokens`.
+    pub fn neighbours(&self) -> &[Neighbours] {
+        &self.neighbours
+    }
+    fn relative<'a>(&self, path: &'a str) -> &'a str {
+        path.strip_prefix(&self.input)
+            .unwrap_or(path)
+            .trim_start_matches('/')
+    }
+    /// True when the file is unchanged and its module is still on disk.
+    pub fn is_fresh(&self, content: &Content, token: &Token) -> bool {
+        let source = self.relative(content.path(token));
+        match (&self.previous, self.current.files.get(source)) {
+            (Some(previous), Some(current)) => {
+                previous.files.get(source) == Some(current)
+                    && self.output.join(&current.output).is_file()
+            }
+            _ => false,
+        }
+    }
+    pub fn collections_fresh(&self, config: &Config) -> bool {
+        self.groups_fresh() && config.output.join(&config.naming.collections).is_file()
+    }
+    pub fn taxonomies_fresh(&self, config: &Config) -> bool {
+        self.groups_fresh() && config.output.join(&config.naming.taxonomies).is_file()
+    }
+    pub fn schemas_fresh(&self, config: &Config) -> bool {
+        self.groups_fresh() && config.output.join(&config.naming.schemas).is_file()
+    }
+    pub fn json_schemas_fresh(&self, config: &Config) -> bool {
+        self.groups_fresh() && config.output.join(&config.naming.json_schemas).is_dir()
+    }
+    pub fn pages_fresh(&self, config: &Config) -> bool {
+        self.groups_fresh() && config.output.join(&config.naming.pages).is_dir()
+    }
+    fn groups_fresh(&self) -> bool {
+        self.previous
+            .as_ref()
+            .map(|p| p.groups == self.current.groups)
+            .unwrap_or(false)
+    }
+    /// Removes the manifest, so the next build starts from scratch.
+    pub fn discard(&self) -> std::io::Result<()> {
+        crate::output::remove_if_exists(&self.output.join(MANIFEST_FILE))?;
+        Ok(())
+    }
+    pub fn save(&self) -> std::io::Result<()> {
+        self.save_manifest(&self.current)
+    }
+    fn save_manifest(&self, manifest: &Manifest) -> std::io::Result<()> {
+        let mut buffer = Vec::new();
+        manifest.write(&mut buffer)?;
+        crate::output::write_if_changed(&self.output.join(MANIFEST_FILE), &buffer)?;
+        Ok(())
+    }
+    /// Saves the manifest without the work that reported problems: the files
+    /// a diagnostic points at, or the groups when a group job reported it or
+    /// it points outside the content. The next build looks at them again,
+    /// otherwise their diagnostics would only ever be reported once.
+    pub fn finish(&self, report: &Report) -> std::io::Result<()> {
+        if report.diagnostics.is_empty() {
+            return self.save();
+        }
+        let mut manifest = self.current.clone();
+        for diagnostic in report.diagnostics.iter() {
+            let source = self.relative(&diagnostic.path);
+            let group = matches!(
+                diagnostic.job,
+                Some("collections" | "taxonomies" | "schemas" | "json-schemas" | "pages")
+            );
+            if group || !self.current.files.contains_key(source) {
+                // No build produces these, so the groups count as changed.
+                manifest.groups = 0;
+            } else {
+                manifest.files.remove(source);
+            }
+        }
+        self.save_manifest(&manifest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cache, Entry, Manifest, Neighbours, MANIFEST_FILE};
+    use crate::{
+        config::Config,
+        diagnostics::{Diagnostic, Report},
+        types::Content,
+    };
+    use std::{collections::HashMap, path::PathBuf};
+
+    #[test]
+    fn round_trips_manifest() {
+        let mut files = HashMap::new();
+        files.insert(
+            "posts/post-1.md".to_owned(),
+            Entry {
+                hash: 42,
+                output: "files/posts_post-1.md.ts".to_owned(),
+            },
+        );
+        let manifest = Manifest {
+            config: 1,
+            groups: 2,
+            files,
+        };
+        let mut out = Vec::new();
+        manifest.write(&mut out).unwrap();
+        let parsed = Manifest::parse(&String::from_utf8(out).unwrap());
+        assert_eq!(parsed, Some(manifest));
+        assert_eq!(Manifest::parse("qwik-city-content 0.0.0 1\n"), None);
+    }
+    #[test]
+    fn body_edits_keep_groups_hash() {
+        let config = Config::new("content".into(), PathBuf::new(), PathBuf::new());
+        let mut before = Content::new();
+        before.push_file("content/a.md", "---\ntags: [a]\n---\nBefore");
+        let mut after = Content::new();
+        after.push_file("content/a.md", "---\ntags: [a]\n---\nAfter");
+        let neighbours = [Neighbours::default()];
+        let before = Manifest::from_content(&config, &before, &neighbours);
+        let after = Manifest::from_content(&config, &after, &neighbours);
+        assert_eq!(before.groups, after.groups);
+        assert_ne!(before.files["a.md"], after.files["a.md"]);
+        assert_eq!(after.files["a.md"].output, "files/a.md.ts");
+    }
+    #[test]
+    fn keeps_the_manifest_of_files_without_problems() {
+        let root = std::env::temp_dir().join(format!("qcc-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let config = Config::new("content".into(), root.clone(), PathBuf::new());
+        let mut content = Content::new();
+        content.push_file("content/a.md", "---\ntags: [\"@\"]\n---\n");
+        content.push_file("content/b.md", "");
+        let cache = Cache::new(&config, &content, true);
+        let mut report = Report::default();
+        let mut warning = Diagnostic::warning("content/a.md", "invalid tag");
+        warning.job = Some("markdown");
+        report.diagnostics.push(warning);
+        cache.finish(&report).unwrap();
+        let saved = Manifest::load(&root.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(saved.groups, cache.current.groups);
+        assert_eq!(saved.files.keys().collect::<Vec<_>>(), vec!["b.md"]);
+        report.diagnostics[0].job = Some("collections");
+        cache.finish(&report).unwrap();
+        let saved = Manifest::load(&root.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(saved.groups, 0);
+        assert_eq!(saved.files.len(), 2);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    config::Config,
+    diagnostics::Diagnostic,
+    html_writer::Markdown,
+    imports::Imports,
+    route_params::RouteParams,
+    types::Content,
+    utils::{html_tag, output_path, write_snake_case},
+};
+
+/// Runs every enabled job up to the point where it would write, collecting
+/// all problems instead of stopping at the first one.
+pub fn check(content: &Content, config: &Arc<Config>) -> Vec<Diagnostic> {
+    let mut problems = Vec::new();
+    let input = config.input.to_string_lossy();
+    let mut outputs: HashMap<PathBuf, &str> = HashMap::with_capacity(content.len());
+    let mut collections: HashMap<String, (&str, String)> = HashMap::new();
+    for token in content.tokens() {
+        let path = content.path(token);
+        let relative = path
+            .strip_prefix(&*input)
+            .unwrap_or(path)
+            .trim_start_matches('/');
+        let mut report = |message: String| problems.push(Diagnostic::new(path, message));
+        // Nested paths are flattened, so `a/b_c.md` and `a_b/c.md` collide.
+        if let Some(other) = outputs.insert(output_path("", relative), path) {
+            report(format!("generates the same module as {}", other));
+        }
+        let tags = crate::jobs::write_collections::tags(content, token, &mut problems);
+        problems.extend(crate::schema::validate(config, content, token));
+        problems.extend(crate::publish::validate(config, content, token));
+        let mut report = |message: String| problems.push(Diagnostic::new(path, message));
+        if config.jobs.collections {
+            for tag in tags {
+                let name = export_name(config.collection(tag).and_then(|c| c.name.as_deref()), tag);
+                match collections.get(&name) {
+                    Some((first, other)) if *other != tag => report(format!(
+                        "collection `{}` is exported as `{}`, like `{}` in {}",
+                        tag, name, other, first
+                    )),
+                    Some(_) => {}
+                    None => {
+                        collections.insert(name, (path, tag.to_owned()));
+                    }
+                }
+            }
+        }
+        let result = if path.ends_with(".mdx") && config.jobs.mdx {
+            check_mdx(content.body_raw(token), config)
+        } else if path.ends_with(".md") && config.jobs.markdown {
+            Markdown::new(config.clone())
+                .write_md(content.body_raw(token))
+                .map(|_| Vec::new())
+                .map_err(|e| format!("could not render markdown: {}", e))
+        } else {
+            Ok(Vec::new())
+        };
+        match result {
+            Ok(warnings) => problems.extend(
+                warnings
+                    .into_iter()
+                    .map(|message| Diagnostic::warning(path, message)),
+            ),
+            Err(message) => report(message),
+        }
+    }
+    if config.jobs.taxonomies {
+        check_taxonomies(content, config, &mut problems);
+    }
+    if config.jobs.route_params && config.routes.is_dir() {
+        check_routes(&config.routes, &mut problems);
+    }
+    problems
+}
+
+/// Renders the MDX body and returns warnings for components that are used
+/// without being imported, since those end up as plain HTML.
+fn check_mdx(body: &str, config: &Arc<Config>) -> Result<Vec<String>, String> {
+    let (imports, body_start) = crate::imports::Parser::new(body)
+        .parse()
+        .unwrap_or((Imports::default(), 0));
+    let body = &body[body_start..];
+    Markdown::new(config.clone())
+        .write_mdx(body, &imports)
+        .map_err(|e| format!("could not render mdx: {}", e))?;
+    let mut messages = Vec::new();
+    for event in pulldown_cmark::Parser::new_ext(body, config.markdown.parser_options()) {
+        if let pulldown_cmark::Event::Html(html) = event {
+            let tag = html_tag(&html);
+            if tag.starts_with(|c: char| c.is_ascii_uppercase()) && !imports.is_import(tag) {
+                messages.push(format!("component `<{}>` is not imported", tag));
+            }
+        }
+    }
+    Ok(messages)
+}
+
+fn check_taxonomies(content: &Content, config: &Config, problems: &mut Vec<Diagnostic>) {
+    let mut taxonomies: HashMap<String, (&str, String)> = HashMap::new();
+    for token in content.tokens() {
+        let path = content.path(token);
+        for key in crate::jobs::write_taxonomies::taxonomies_of(config, path) {
+            let name = export_name(config.taxonomy(key).and_then(|c| c.name.as_deref()), key);
+            match taxonomies.get(&name) {
+                Some((first, other)) if *other != key => problems.push(Diagnostic::new(
+                    path,
+                    format!(
+                        "taxonomy `{}` is exported as `{}`, like `{}` in {}",
+                        key, name, other, first
+                    ),
+                )),
+                Some(_) => {}
+                None => {
+                    taxonomies.insert(name, (path, key.to_owned()));
+                }
+            }
+        }
+    }
+}
+
+/// Reports routes whose params would produce an invalid `RouteParams` interface.
+fn check_routes(root: &Path, problems: &mut Vec<Diagnostic>) {
+    let dir = match std::fs::read_dir(root) {
+        Ok(dir) => dir,
+        Err(e) => {
+            problems.push(Diagnostic::new(
+                root.display().to_string(),
+                format!("could not read routes: {}", e),
+            ));
+            return;
+        }
+    };
+    for entry in dir.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            check_routes(&path, problems);
+        } else if path.file_stem().map(|s| s == "index") == Some(true) {
+            let mut seen: Vec<String> = Vec::new();
+            for param in RouteParams::from_path(&path) {
+                let message = if param.is_empty() {
+                    "route has an empty param `[]`".to_owned()
+                } else if seen.contains(&param) {
+                    format!("route param `{}` is used more than once", param)
+                } else {
+                    seen.push(param);
+                    continue;
+                };
+                problems.push(Diagnostic::new(path.display().to_string(), message));
+            }
+        }
+    }
+}
+
+/// The identifier a collection or taxonomy is exported under.
+fn export_name(name: Option<&str>, key: &str) -> String {
+    let mut snake = Vec::with_capacity(key.len());
+    let _ = write_snake_case(name.unwrap_or(key), &mut snake);
+    String::from_utf8_lossy(&snake).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, sync::Arc};
+
+    use super::check;
+    use crate::{config::Config, types::Content};
+
+    #[test]
+    fn reports_every_problem() {
+        let config = Arc::new(Config::new(
+            "content".into(),
+            "content-generated".into(),
+            PathBuf::from("missing-routes"),
+        ));
+        let mut content = Content::new();
+        content.push_file("content/ok.md", "---\ntags: [a, b]\n---\n# Fine");
+        content.push_file("content/tags.md", "---\ntags: a\n---\n");
+        content.push_file("content/quote.md", "---\ntitle: \"open\n---\n");
+        content.push_file("content/bad-tag.md", "---\ntags: [a.b]\n---\n");
+        content.push_file("content/clash.md", "---\ntags: [a-b, a b]\n---\n");
+        content.push_file(
+            "content/page.mdx",
+            "import Counter from \"./counter\"\n\n<Counter />\n\n<Missing />\n",
+        );
+        let problems: Vec<String> = check(&content, &config)
+            .iter()
+            .map(|p| format!("{}: {}", p.level, p))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                "warning: content/tags.md: `tags` must be a list, found `a`",
+                "error: content/quote.md: invalid frontmatter: expected `\"`",
+                "warning: content/bad-tag.md: invalid tag `a.b` is ignored, tags must start with a letter and contain only letters, digits, spaces, `-` and `_`",
+                "error: content/clash.md: collection `a b` is exported as `a_b`, like `a-b` in content/clash.md",
+                "warning: content/page.mdx: component `<Missing>` is not imported",
+            ]
+        );
+    }
+}
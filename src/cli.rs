@@ -0,0 +1,245 @@
+use std::{fmt::Display, path::PathBuf};
+
+use qwik_city_content::{date::Timestamp, Config};
+
+pub const USAGE: &str = "\
+Usage: qwik-city-content [COMMAND] [OPTIONS]
+
+Commands:
+  build    Generate content modules and route params (default)
+  watch    Rebuild whenever content or routes change
+  check    Validate content without writing any output
+  serve    Answer line-delimited JSON requests on stdin, for dev server plugins
+  clean    Remove all generated files
+
+Options:
+  -c, --config <FILE>    Config file [default: nearest qwik-content.toml]
+  -i, --input <DIR>      Content directory [default: src/content]
+  -o, --output <DIR>     Generated output directory [default: src/content-generated]
+  -r, --routes <DIR>     Qwik City routes directory [default: src/routes]
+  -j, --threads <N>      Number of worker threads [default: available cores]
+  -f, --force            Ignore the build cache and regenerate everything
+      --drafts           Include draft pages, always on for watch and serve
+      --now <DATE>       Publish as of this date instead of the current time
+      --deny-warnings    Fail when content produces warnings
+      --report <FILE>    Write a JSON build report
+  -h, --help             Print this message
+  -V, --version          Print version
+
+Exit status:
+  0  Success
+  1  Fatal error, e.g. an invalid config file
+  2  Invalid command line
+  3  Content has errors, or warnings with --deny-warnings
+";
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Command {
+    #[default]
+    Build,
+    Watch,
+    Check,
+    Serve,
+    Clean,
+    Help,
+    Version,
+}
+
+/// Parsed arguments. Options left as `None` fall back to the config file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub config: Option<PathBuf>,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub routes: Option<PathBuf>,
+    pub threads: Option<usize>,
+    pub force: bool,
+    pub drafts: bool,
+    pub now: Option<Timestamp>,
+    pub deny_warnings: bool,
+    pub report: Option<PathBuf>,
+}
+
+impl Args {
+    /// Applies the command-line overrides on top of `config`.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(input) = &self.input {
+            config.input = input.clone();
+        }
+        if let Some(output) = &self.output {
+            config.output = output.clone();
+        }
+        if let Some(routes) = &self.routes {
+            config.routes = routes.clone();
+        }
+        if let Some(threads) = self.threads {
+            config.threads = threads;
+        }
+        if self.drafts {
+            config.drafts = true;
+        }
+        if self.now.is_some() {
+            config.now = self.now;
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliError {
+    UnknownCommand(String),
+    UnknownOption(String),
+    MissingValue(&'static str),
+    InvalidThreads(String),
+    InvalidDate(String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownCommand(cmd) => write!(f, "unknown command `{}`", cmd),
+            CliError::UnknownOption(opt) => write!(f, "unknown option `{}`", opt),
+            CliError::MissingValue(opt) => write!(f, "option `{}` requires a value", opt),
+            CliError::InvalidThreads(value) => {
+                write!(f, "`{}` is not a valid thread count", value)
+            }
+            CliError::InvalidDate(value) => write!(
+                f,
+                "`{}` is not a date like `2022-08-22` or `2022-08-22T10:00:00Z`",
+                value
+            ),
+        }
+    }
+}
+
+/// Parses the arguments following the program name.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, CliError> {
+    let mut parsed = Args::default();
+    let mut command: Option<Command> = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Support both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_owned(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &'static str| -> Result<String, CliError> {
+            match inline {
+                Some(value) => Ok(value.to_owned()),
+                None => args.next().ok_or(CliError::MissingValue(name)),
+            }
+        };
+        match flag.as_str() {
+            "-h" | "--help" => command = Some(Command::Help),
+            "-V" | "--version" => command = Some(Command::Version),
+            "-f" | "--force" => parsed.force = true,
+            "--drafts" => parsed.drafts = true,
+            "--now" => {
+                let now = value("--now")?;
+                match qwik_city_content::date::parse(&now) {
+                    Some(date) => parsed.now = Some(date),
+                    None => return Err(CliError::InvalidDate(now)),
+                }
+            }
+            "--deny-warnings" => parsed.deny_warnings = true,
+            "--report" => parsed.report = Some(value("--report")?.into()),
+            "-c" | "--config" => parsed.config = Some(value("--config")?.into()),
+            "-i" | "--input" => parsed.input = Some(value("--input")?.into()),
+            "-o" | "--output" => parsed.output = Some(value("--output")?.into()),
+            "-r" | "--routes" => parsed.routes = Some(value("--routes")?.into()),
+            "-j" | "--threads" => {
+                let threads = value("--threads")?;
+                parsed.threads = match threads.parse::<usize>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(CliError::InvalidThreads(threads)),
+                };
+            }
+            opt if opt.starts_with('-') => return Err(CliError::UnknownOption(opt.to_owned())),
+            cmd if command.is_none() => {
+                command = Some(match cmd {
+                    "build" => Command::Build,
+                    "watch" => Command::Watch,
+                    "check" => Command::Check,
+                    "serve" => Command::Serve,
+                    "clean" => Command::Clean,
+                    "help" => Command::Help,
+                    _ => return Err(CliError::UnknownCommand(cmd.to_owned())),
+                })
+            }
+            cmd => return Err(CliError::UnknownCommand(cmd.to_owned())),
+        }
+    }
+    parsed.command = command.unwrap_or(Command::Build);
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{parse, CliError, Command};
+    use qwik_city_content::Config;
+
+    fn args(src: &str) -> Vec<String> {
+        src.split_whitespace().map(|s| s.to_owned()).collect()
+    }
+
+    #[test]
+    fn defaults_to_build() {
+        let parsed = parse(args("")).unwrap();
+        assert_eq!(parsed.command, Command::Build);
+        assert_eq!(parsed.input, None);
+        assert_eq!(parse(args("serve")).unwrap().command, Command::Serve);
+    }
+    #[test]
+    fn parses_subcommand_and_flags() {
+        let parsed = parse(args(
+            "watch -i content --output=out -r app/routes -j 2 --deny-warnings --report r.json",
+        ))
+        .unwrap();
+        assert_eq!(parsed.command, Command::Watch);
+        assert_eq!(parsed.input, Some(PathBuf::from("content")));
+        assert_eq!(parsed.output, Some(PathBuf::from("out")));
+        assert_eq!(parsed.routes, Some(PathBuf::from("app/routes")));
+        assert_eq!(parsed.threads, Some(2));
+        assert!(parsed.deny_warnings);
+        assert_eq!(parsed.report, Some(PathBuf::from("r.json")));
+    }
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(
+            parse(args("deploy")),
+            Err(CliError::UnknownCommand("deploy".to_owned()))
+        );
+        assert_eq!(
+            parse(args("build --fast")),
+            Err(CliError::UnknownOption("--fast".to_owned()))
+        );
+        assert_eq!(
+            parse(args("build --input")),
+            Err(CliError::MissingValue("--input"))
+        );
+        assert_eq!(
+            parse(args("-j 0")),
+            Err(CliError::InvalidThreads("0".to_owned()))
+        );
+        assert_eq!(
+            parse(args("--now tomorrow")),
+            Err(CliError::InvalidDate("tomorrow".to_owned()))
+        );
+    }
+    #[test]
+    fn overrides_config() {
+        let mut config = Config::with_root(&PathBuf::from("site"));
+        parse(args(
+            "-c site/qwik-content.toml -o out -j 3 --drafts --now 2030-01-01",
+        ))
+        .unwrap()
+        .apply(&mut config);
+        assert_eq!(config.input, PathBuf::from("site/src/content"));
+        assert_eq!(config.output, PathBuf::from("out"));
+        assert_eq!(config.threads, 3);
+        assert!(config.drafts);
+        assert_eq!(config.now, Some(1893456000));
+    }
+}
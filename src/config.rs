@@ -0,0 +1,914 @@
+use std::{
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    date::Timestamp,
+    schema::{Field, FieldType, Literal, Schema},
+    sort::SortKey,
+    toml::{Table, TomlError, Value},
+};
+
+/// Name of the project configuration file, looked up from the working
+/// directory upwards.
+pub const CONFIG_FILE: &str = "qwik-content.toml";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub routes: PathBuf,
+    pub threads: usize,
+    /// Include pages marked `draft: true`, which are left out by default.
+    pub drafts: bool,
+    /// The moment scheduled and expired pages are judged against, the
+    /// system clock when `None`.
+    pub now: Option<Timestamp>,
+    pub publishing: Publishing,
+    /// Reading speed behind the `_readingTime` of every page.
+    pub words_per_minute: usize,
+    /// Paragraphs in the `_excerpt` of pages without a `<!-- more -->`.
+    pub excerpt_paragraphs: usize,
+    /// Order of every collection and taxonomy that does not set its own.
+    pub sort: Vec<SortKey>,
+    pub jobs: Jobs,
+    pub markdown: MarkdownOptions,
+    pub toc: TocOptions,
+    pub anchors: AnchorOptions,
+    pub naming: Naming,
+    pub collections: Vec<GroupConfig>,
+    pub taxonomies: Vec<GroupConfig>,
+    pub pagination: Vec<Pagination>,
+    pub navigation: Navigation,
+}
+
+/// Toggles for the jobs run by a build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jobs {
+    pub markdown: bool,
+    pub mdx: bool,
+    pub collections: bool,
+    pub taxonomies: bool,
+    pub route_params: bool,
+    /// Zod schemas mirroring the generated types, off by default since
+    /// they need `zod` installed.
+    pub schemas: bool,
+    /// A JSON Schema of the frontmatter of each group, for editors.
+    pub json_schemas: bool,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self {
+            markdown: true,
+            mdx: true,
+            collections: true,
+            taxonomies: true,
+            route_params: true,
+            schemas: false,
+            json_schemas: false,
+        }
+    }
+}
+
+/// Markdown extensions passed on to pulldown-cmark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    pub tables: bool,
+    pub footnotes: bool,
+    pub strikethrough: bool,
+    pub tasklists: bool,
+    pub smart_punctuation: bool,
+    /// `{#id .class}` after a heading's text.
+    pub heading_attributes: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            footnotes: true,
+            strikethrough: true,
+            tasklists: true,
+            smart_punctuation: false,
+            heading_attributes: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    pub fn parser_options(&self) -> pulldown_cmark::Options {
+        let mut options = pulldown_cmark::Options::empty();
+        options.set(pulldown_cmark::Options::ENABLE_TABLES, self.tables);
+        options.set(pulldown_cmark::Options::ENABLE_FOOTNOTES, self.footnotes);
+        options.set(
+            pulldown_cmark::Options::ENABLE_STRIKETHROUGH,
+            self.strikethrough,
+        );
+        options.set(pulldown_cmark::Options::ENABLE_TASKLISTS, self.tasklists);
+        options.set(
+            pulldown_cmark::Options::ENABLE_SMART_PUNCTUATION,
+            self.smart_punctuation,
+        );
+        options.set(
+            pulldown_cmark::Options::ENABLE_HEADING_ATTRIBUTES,
+            self.heading_attributes,
+        );
+        options
+    }
+}
+
+/// Frontmatter fields that schedule when a page is published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Publishing {
+    /// Pages dated in the future are left out until then.
+    pub publish_date: String,
+    /// Pages are left out from this date on.
+    pub expiry_date: String,
+}
+
+impl Default for Publishing {
+    fn default() -> Self {
+        Self {
+            publish_date: "publishDate".to_owned(),
+            expiry_date: "expiryDate".to_owned(),
+        }
+    }
+}
+
+/// Heading levels included in the `_toc` of every page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocOptions {
+    pub min_depth: usize,
+    pub max_depth: usize,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            min_depth: 2,
+            max_depth: 3,
+        }
+    }
+}
+
+/// Self-links added to every heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorOptions {
+    /// Where the link goes inside the heading, no link when `None`.
+    pub placement: Option<AnchorPlacement>,
+    pub class: String,
+}
+
+impl Default for AnchorOptions {
+    fn default() -> Self {
+        Self {
+            placement: None,
+            class: "anchor".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorPlacement {
+    Before,
+    After,
+}
+
+/// File names of the generated output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Naming {
+    pub files: String,
+    pub collections: String,
+    pub taxonomies: String,
+    pub schemas: String,
+    /// Directory of the JSON Schemas, inside the output directory.
+    pub json_schemas: String,
+    /// Directory of the page modules, inside the output directory.
+    pub pages: String,
+    pub route_params: String,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            files: "files".to_owned(),
+            collections: "collections.ts".to_owned(),
+            taxonomies: "taxonomies.ts".to_owned(),
+            schemas: "schemas.ts".to_owned(),
+            json_schemas: "json-schemas".to_owned(),
+            pages: "pages".to_owned(),
+            route_params: "generated.ts".to_owned(),
+        }
+    }
+}
+
+/// Settings for a single collection (tag) or taxonomy (directory).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupConfig {
+    pub key: String,
+    /// Replaces the key when deriving the exported const and type names.
+    pub name: Option<String>,
+    /// Checked against the frontmatter of every file in the group.
+    pub schema: Option<Schema>,
+    /// Replaces [`Config::sort`] for the group.
+    pub sort: Option<Vec<SortKey>>,
+}
+
+/// A collection or taxonomy split into page modules of `per_page` items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pagination {
+    /// Directory of the page modules, inside `naming.pages`.
+    pub name: String,
+    pub group: GroupRef,
+    pub per_page: usize,
+}
+
+/// Where the `_prev` and `_next` of every page point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Navigation {
+    /// The group pages are chained in, the taxonomy of each page's
+    /// top-level directory when `None`.
+    pub group: Option<GroupRef>,
+    /// Template of the `url` of a link, with `{directory}`, `{slug}` and
+    /// `{id}` filled in.
+    pub url: String,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Self {
+            group: None,
+            url: "/{directory}/{slug}".to_owned(),
+        }
+    }
+}
+
+/// A collection (tag) or taxonomy (directory), by key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupRef {
+    Collection(String),
+    Taxonomy(String),
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(TomlError),
+    Invalid(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::Parse(e) => write!(f, "{}", e),
+            ConfigError::Invalid(e) => f.write_str(e),
+        }
+    }
+}
+
+impl Config {
+    pub fn new(input: PathBuf, output: PathBuf, routes: PathBuf) -> Self {
+        Self {
+            input,
+            output,
+            routes,
+            threads: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+            drafts: false,
+            now: None,
+            publishing: Publishing::default(),
+            words_per_minute: 200,
+            excerpt_paragraphs: 1,
+            sort: SortKey::defaults(),
+            jobs: Jobs::default(),
+            markdown: MarkdownOptions::default(),
+            toc: TocOptions::default(),
+            anchors: AnchorOptions::default(),
+            naming: Naming::default(),
+            collections: Vec::new(),
+            taxonomies: Vec::new(),
+            pagination: Vec::new(),
+            navigation: Navigation::default(),
+        }
+    }
+    /// Default configuration for a Qwik City project rooted at `root`.
+    pub fn with_root(root: &Path) -> Self {
+        Self::new(
+            root.join("src/content"),
+            root.join("src/content-generated"),
+            root.join("src/routes"),
+        )
+    }
+    /// Searches `start` and its ancestors for a `qwik-content.toml`.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let src = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::from_toml(&src, path.parent().unwrap_or(Path::new("")))
+    }
+    /// Parses a config file, resolving relative paths against `root`.
+    pub fn from_toml(src: &str, root: &Path) -> Result<Self, ConfigError> {
+        let table = crate::toml::Parser::from_str(src)
+            .parse()
+            .map_err(ConfigError::Parse)?;
+        Self::from_table(&table, root).map_err(ConfigError::Invalid)
+    }
+    fn from_table(table: &Table, root: &Path) -> Result<Self, String> {
+        let mut config = Self::with_root(root);
+        let top = Section::new(table, "");
+        top.allow(&[
+            "input",
+            "output",
+            "routes",
+            "threads",
+            "drafts",
+            "publishing",
+            "words_per_minute",
+            "excerpt_paragraphs",
+            "sort",
+            "jobs",
+            "markdown",
+            "toc",
+            "anchors",
+            "naming",
+            "collections",
+            "taxonomies",
+            "pagination",
+            "navigation",
+        ])?;
+        if let Some(input) = top.string("input")? {
+            config.input = root.join(input);
+        }
+        if let Some(output) = top.string("output")? {
+            config.output = root.join(output);
+        }
+        if let Some(routes) = top.string("routes")? {
+            config.routes = root.join(routes);
+        }
+        if let Some(threads) = top.positive("threads")? {
+            config.threads = threads;
+        }
+        top.bool_into("drafts", &mut config.drafts)?;
+        if let Some(publishing) = top.table("publishing")? {
+            publishing.allow(&["publish_date", "expiry_date"])?;
+            let defaults = &mut config.publishing;
+            publishing.string_into("publish_date", &mut defaults.publish_date)?;
+            publishing.string_into("expiry_date", &mut defaults.expiry_date)?;
+        }
+        if let Some(words) = top.positive("words_per_minute")? {
+            config.words_per_minute = words;
+        }
+        if let Some(paragraphs) = top.positive("excerpt_paragraphs")? {
+            config.excerpt_paragraphs = paragraphs;
+        }
+        if let Some(sort) = top.sort_keys("sort")? {
+            config.sort = sort;
+        }
+        if let Some(jobs) = top.table("jobs")? {
+            jobs.allow(&[
+                "markdown",
+                "mdx",
+                "collections",
+                "taxonomies",
+                "route_params",
+                "schemas",
+                "json_schemas",
+            ])?;
+            let defaults = &mut config.jobs;
+            jobs.bool_into("markdown", &mut defaults.markdown)?;
+            jobs.bool_into("mdx", &mut defaults.mdx)?;
+            jobs.bool_into("collections", &mut defaults.collections)?;
+            jobs.bool_into("taxonomies", &mut defaults.taxonomies)?;
+            jobs.bool_into("route_params", &mut defaults.route_params)?;
+            jobs.bool_into("schemas", &mut defaults.schemas)?;
+            jobs.bool_into("json_schemas", &mut defaults.json_schemas)?;
+        }
+        if let Some(markdown) = top.table("markdown")? {
+            markdown.allow(&[
+                "tables",
+                "footnotes",
+                "strikethrough",
+                "tasklists",
+                "smart_punctuation",
+                "heading_attributes",
+            ])?;
+            let defaults = &mut config.markdown;
+            markdown.bool_into("tables", &mut defaults.tables)?;
+            markdown.bool_into("footnotes", &mut defaults.footnotes)?;
+            markdown.bool_into("strikethrough", &mut defaults.strikethrough)?;
+            markdown.bool_into("tasklists", &mut defaults.tasklists)?;
+            markdown.bool_into("smart_punctuation", &mut defaults.smart_punctuation)?;
+            markdown.bool_into("heading_attributes", &mut defaults.heading_attributes)?;
+        }
+        if let Some(anchors) = top.table("anchors")? {
+            anchors.allow(&["placement", "class"])?;
+            let defaults = &mut config.anchors;
+            match anchors.string("placement")? {
+                Some("before") => defaults.placement = Some(AnchorPlacement::Before),
+                Some("after") => defaults.placement = Some(AnchorPlacement::After),
+                Some("none") => defaults.placement = None,
+                Some(other) => {
+                    return Err(format!(
+                        "`anchors.placement` must be one of `before`, `after`, `none`, found `{}`",
+                        other
+                    ))
+                }
+                None => {}
+            }
+            anchors.string_into("class", &mut defaults.class)?;
+        }
+        if let Some(toc) = top.table("toc")? {
+            toc.allow(&["min_depth", "max_depth"])?;
+            let defaults = &mut config.toc;
+            for (key, out) in [
+                ("min_depth", &mut defaults.min_depth),
+                ("max_depth", &mut defaults.max_depth),
+            ] {
+                match toc.positive(key)? {
+                    Some(depth) if depth > 6 => {
+                        return Err(format!(
+                            "`{}` must be a heading level from 1 to 6, found {}",
+                            toc.path(key),
+                            depth
+                        ))
+                    }
+                    Some(depth) => *out = depth,
+                    None => {}
+                }
+            }
+            if defaults.min_depth > defaults.max_depth {
+                return Err("`toc.min_depth` must not be greater than `toc.max_depth`".to_owned());
+            }
+        }
+        if let Some(naming) = top.table("naming")? {
+            naming.allow(&[
+                "files",
+                "collections",
+                "taxonomies",
+                "schemas",
+                "json_schemas",
+                "pages",
+                "route_params",
+            ])?;
+            let defaults = &mut config.naming;
+            // Stale modules are removed from these directories.
+            naming.dir_into("files", &mut defaults.files)?;
+            naming.string_into("collections", &mut defaults.collections)?;
+            naming.string_into("taxonomies", &mut defaults.taxonomies)?;
+            naming.string_into("schemas", &mut defaults.schemas)?;
+            naming.dir_into("json_schemas", &mut defaults.json_schemas)?;
+            naming.dir_into("pages", &mut defaults.pages)?;
+            naming.string_into("route_params", &mut defaults.route_params)?;
+        }
+        if let Some(collections) = top.table("collections")? {
+            config.collections = parse_groups(&collections)?;
+        }
+        if let Some(taxonomies) = top.table("taxonomies")? {
+            config.taxonomies = parse_groups(&taxonomies)?;
+        }
+        if let Some(pagination) = top.table("pagination")? {
+            config.pagination = parse_pagination(&pagination)?;
+        }
+        if let Some(navigation) = top.table("navigation")? {
+            navigation.allow(&["collection", "taxonomy", "url"])?;
+            config.navigation.group = match (
+                navigation.string("collection")?,
+                navigation.string("taxonomy")?,
+            ) {
+                (Some(tag), None) => Some(GroupRef::Collection(tag.to_owned())),
+                (None, Some(key)) => Some(GroupRef::Taxonomy(key.to_owned())),
+                (None, None) => None,
+                (Some(_), Some(_)) => {
+                    return Err(
+                        "`navigation` takes either a `collection` or a `taxonomy`".to_owned()
+                    )
+                }
+            };
+            navigation.string_into("url", &mut config.navigation.url)?;
+        }
+        Ok(config)
+    }
+    pub fn collection(&self, key: &str) -> Option<&GroupConfig> {
+        self.collections.iter().find(|c| c.key == key)
+    }
+    pub fn taxonomy(&self, key: &str) -> Option<&GroupConfig> {
+        self.taxonomies.iter().find(|c| c.key == key)
+    }
+    /// The order of `group`, or of groups without a config.
+    pub fn sort_keys<'a>(&'a self, group: Option<&'a GroupConfig>) -> &'a [SortKey] {
+        group.and_then(|g| g.sort.as_deref()).unwrap_or(&self.sort)
+    }
+    /// Hash of every setting that affects generated output. Changing any of
+    /// them invalidates the build cache.
+    pub fn fingerprint(&self) -> u64 {
+        let settings = Self {
+            threads: 0,
+            ..self.clone()
+        };
+        let version = env!("CARGO_PKG_VERSION");
+        crate::utils::stable_hash(format!("{}{:?}", version, settings).as_bytes())
+    }
+    pub fn files_dir(&self) -> PathBuf {
+        self.output.join(&self.naming.files)
+    }
+}
+
+fn parse_groups(section: &Section) -> Result<Vec<GroupConfig>, String> {
+    let mut groups = Vec::new();
+    for (key, _) in section.table.iter() {
+        let group = section
+            .table(key)?
+            .ok_or_else(|| format!("`{}` must be a table", section.path(key)))?;
+        group.allow(&["name", "schema", "sort"])?;
+        groups.push(GroupConfig {
+            key: key.to_owned(),
+            name: group.string("name")?.map(|s| s.to_owned()),
+            schema: group
+                .table("schema")?
+                .map(|s| parse_schema(&s))
+                .transpose()?,
+            sort: group.sort_keys("sort")?,
+        });
+    }
+    Ok(groups)
+}
+
+/// Listings are tables like `[pagination.posts]` with `collection = "blog"`
+/// or `taxonomy = "posts"`, and `per_page`.
+fn parse_pagination(section: &Section) -> Result<Vec<Pagination>, String> {
+    let mut listings = Vec::new();
+    for (name, _) in section.table.iter() {
+        let listing = section
+            .table(name)?
+            .ok_or_else(|| format!("`{}` must be a table", section.path(name)))?;
+        // The name becomes a directory.
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            return Err(format!(
+                "`{}` must be a plain directory name",
+                section.path(name)
+            ));
+        }
+        listing.allow(&["collection", "taxonomy", "per_page"])?;
+        let group = match (listing.string("collection")?, listing.string("taxonomy")?) {
+            (Some(tag), None) => GroupRef::Collection(tag.to_owned()),
+            (None, Some(key)) => GroupRef::Taxonomy(key.to_owned()),
+            _ => {
+                return Err(format!(
+                    "`{}` needs either a `collection` or a `taxonomy`",
+                    section.path(name)
+                ))
+            }
+        };
+        let per_page = listing
+            .positive("per_page")?
+            .ok_or_else(|| format!("`{}` is missing", listing.path("per_page")))?;
+        listings.push(Pagination {
+            name: name.to_owned(),
+            group,
+            per_page,
+        });
+    }
+    Ok(listings)
+}
+
+/// Fields are inline tables like `title = { type = "string", required = true }`.
+fn parse_schema(section: &Section) -> Result<Schema, String> {
+    let mut fields = Vec::new();
+    for (name, _) in section.table.iter() {
+        let field = section
+            .table(name)?
+            .ok_or_else(|| format!("`{}` must be a table", section.path(name)))?;
+        field.allow(&["type", "required", "enum", "default"])?;
+        let type_name = field
+            .string("type")?
+            .ok_or_else(|| format!("`{}` is missing", field.path("type")))?;
+        let kind = FieldType::from_name(type_name).ok_or_else(|| {
+            format!(
+                "`{}` must be one of `string`, `number`, `integer`, `boolean`, `date`, `list` or `object`, found `{}`",
+                field.path("type"),
+                type_name
+            )
+        })?;
+        let mut required = false;
+        field.bool_into("required", &mut required)?;
+        let mut allowed = Vec::new();
+        match field.table.get("enum") {
+            None => {}
+            Some(Value::Array(values)) => {
+                for value in values {
+                    allowed.push(field.literal("enum", value, kind)?);
+                }
+            }
+            Some(v) => return Err(field.expected("enum", "array", v)),
+        }
+        let default = match field.table.get("default") {
+            None => None,
+            Some(_) if required => {
+                return Err(format!(
+                    "`{}` is never used, `{}` is required",
+                    field.path("default"),
+                    section.path(name)
+                ))
+            }
+            Some(value) => Some(field.literal("default", value, kind)?),
+        };
+        fields.push(Field {
+            name: name.to_owned(),
+            kind,
+            required,
+            allowed,
+            default,
+        });
+    }
+    Ok(Schema { fields })
+}
+
+/// A table in the config file along with its dotted path, for error messages.
+struct Section<'a> {
+    table: &'a Table,
+    name: String,
+}
+
+impl<'a> Section<'a> {
+    fn new(table: &'a Table, name: &str) -> Self {
+        Self {
+            table,
+            name: name.to_owned(),
+        }
+    }
+    fn path(&self, key: &str) -> String {
+        if self.name.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", self.name, key)
+        }
+    }
+    fn allow(&self, keys: &[&str]) -> Result<(), String> {
+        match self.table.iter().find(|(k, _)| !keys.contains(k)) {
+            Some((key, _)) => Err(format!("unknown key `{}`", self.path(key))),
+            None => Ok(()),
+        }
+    }
+    fn expected(&self, key: &str, expected: &str, found: &Value) -> String {
+        format!(
+            "`{}` must be a {}, found {}",
+            self.path(key),
+            expected,
+            found.type_name()
+        )
+    }
+    fn string(&self, key: &str) -> Result<Option<&'a str>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(v) => Err(self.expected(key, "string", v)),
+        }
+    }
+    fn string_into(&self, key: &str, out: &mut String) -> Result<(), String> {
+        if let Some(s) = self.string(key)? {
+            *out = s.to_owned();
+        }
+        Ok(())
+    }
+    /// A directory inside the output directory, which must not be the output
+    /// directory itself or lead out of it.
+    fn dir_into(&self, key: &str, out: &mut String) -> Result<(), String> {
+        if let Some(s) = self.string(key)? {
+            let inside = Path::new(s)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+            if s.is_empty() || !inside {
+                return Err(format!(
+                    "`{}` must be a relative path inside the output directory, found `{}`",
+                    self.path(key),
+                    s
+                ));
+            }
+            *out = s.to_owned();
+        }
+        Ok(())
+    }
+    fn bool_into(&self, key: &str, out: &mut bool) -> Result<(), String> {
+        match self.table.get(key) {
+            None => Ok(()),
+            Some(Value::Bool(b)) => {
+                *out = *b;
+                Ok(())
+            }
+            Some(v) => Err(self.expected(key, "boolean", v)),
+        }
+    }
+    /// A value of `key` that must fit a field of type `kind`.
+    fn literal(&self, key: &str, value: &Value, kind: FieldType) -> Result<Literal, String> {
+        if !kind.is_scalar() {
+            return Err(format!(
+                "`{}` is not supported for fields of type {}",
+                self.path(key),
+                kind
+            ));
+        }
+        let literal = match value {
+            Value::String(s) => Some(Literal::String(s.clone())),
+            Value::Integer(n) => Some(Literal::Number(n.to_string())),
+            Value::Float(n) => Some(Literal::Number(n.to_string())),
+            Value::Bool(b) => Some(Literal::Bool(*b)),
+            _ => None,
+        };
+        match literal {
+            Some(literal) if literal.is_a(kind) => Ok(literal),
+            _ => Err(format!(
+                "`{}` must be {}, found {}",
+                self.path(key),
+                kind,
+                value.type_name()
+            )),
+        }
+    }
+    /// Field names like `["-date", "title"]`.
+    fn sort_keys(&self, key: &str) -> Result<Option<Vec<SortKey>>, String> {
+        let values = match self.table.get(key) {
+            None => return Ok(None),
+            Some(Value::Array(values)) => values,
+            Some(v) => return Err(self.expected(key, "list of field names", v)),
+        };
+        let mut keys = Vec::with_capacity(values.len());
+        for value in values {
+            match value {
+                Value::String(s) => keys.push(SortKey::parse(s).ok_or_else(|| {
+                    format!(
+                        "`{}` must list field names like `title` or `-date`, found `{}`",
+                        self.path(key),
+                        s
+                    )
+                })?),
+                v => return Err(self.expected(key, "list of field names", v)),
+            }
+        }
+        Ok(Some(keys))
+    }
+    fn positive(&self, key: &str) -> Result<Option<usize>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::Integer(n)) if *n > 0 => Ok(Some(*n as usize)),
+            Some(v) => Err(self.expected(key, "positive integer", v)),
+        }
+    }
+    fn table(&self, key: &str) -> Result<Option<Section<'a>>, String> {
+        match self.table.get(key) {
+            None => Ok(None),
+            Some(Value::Table(table)) => Ok(Some(Section {
+                table,
+                name: self.path(key),
+            })),
+            Some(v) => Err(self.expected(key, "table", v)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{AnchorPlacement, Config, ConfigError, GroupRef, Pagination};
+    use crate::sort::SortKey;
+
+    #[test]
+    fn defaults_to_qwik_city_layout() {
+        let config = Config::from_toml("", Path::new("site")).unwrap();
+        assert_eq!(config.input, PathBuf::from("site/src/content"));
+        assert_eq!(config.output, PathBuf::from("site/src/content-generated"));
+        assert_eq!(config.routes, PathBuf::from("site/src/routes"));
+        assert!(config.jobs.route_params);
+    }
+    #[test]
+    fn reads_config_file() {
+        let src = [
+            "input = \"content\"",
+            "threads = 2",
+            "words_per_minute = 250",
+            "sort = [\"title\"]",
+            "[jobs]",
+            "route_params = false",
+            "[markdown]",
+            "smart_punctuation = true",
+            "[anchors]",
+            "placement = \"after\"",
+            "[publishing]",
+            "publish_date = \"date\"",
+            "[naming]",
+            "collections = \"all.ts\"",
+            "[collections.\"qwik city\"]",
+            "name = \"qwik\"",
+            "sort = [\"-weight\", \"title\"]",
+            "[pagination.posts]",
+            "taxonomy = \"posts\"",
+            "per_page = 10",
+            "[navigation]",
+            "collection = \"blog\"",
+            "url = \"/post/{id}\"",
+        ]
+        .join("\n");
+        let config = Config::from_toml(&src, Path::new("site")).unwrap();
+        assert_eq!(config.input, PathBuf::from("site/content"));
+        assert_eq!(config.threads, 2);
+        assert_eq!(config.words_per_minute, 250);
+        assert!(!config.jobs.route_params);
+        assert!(config.markdown.smart_punctuation);
+        assert_eq!(config.anchors.placement, Some(AnchorPlacement::After));
+        assert_eq!(config.publishing.publish_date, "date");
+        assert_eq!(config.publishing.expiry_date, "expiryDate");
+        assert_eq!(config.naming.collections, "all.ts");
+        let collection = config.collection("qwik city").unwrap();
+        assert_eq!(collection.name.as_deref(), Some("qwik"));
+        let sort = |keys: &[SortKey]| -> Vec<(String, bool)> {
+            keys.iter()
+                .map(|k| (k.field.clone(), k.descending))
+                .collect()
+        };
+        assert_eq!(
+            sort(config.sort_keys(Some(collection))),
+            vec![("weight".to_owned(), true), ("title".to_owned(), false)]
+        );
+        assert_eq!(
+            sort(config.sort_keys(None)),
+            vec![("title".to_owned(), false)]
+        );
+        assert_eq!(
+            config.pagination,
+            vec![Pagination {
+                name: "posts".to_owned(),
+                group: GroupRef::Taxonomy("posts".to_owned()),
+                per_page: 10,
+            }]
+        );
+        assert_eq!(
+            config.navigation.group,
+            Some(GroupRef::Collection("blog".to_owned()))
+        );
+        assert_eq!(config.navigation.url, "/post/{id}");
+    }
+    #[test]
+    fn rejects_unknown_and_mistyped_keys() {
+        let err = Config::from_toml("[jobs]\nfeeds = true", Path::new("")).err();
+        assert!(matches!(err, Some(ConfigError::Invalid(e)) if e == "unknown key `jobs.feeds`"));
+        let err = Config::from_toml("threads = \"many\"", Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`threads` must be a positive integer, found string"
+        ));
+        let err = Config::from_toml("[toc]\nmin_depth = 4", Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`toc.min_depth` must not be greater than `toc.max_depth`"
+        ));
+        let err = Config::from_toml("sort = [\"-\"]", Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`sort` must list field names like `title` or `-date`, found `-`"
+        ));
+        let src = "[pagination.posts]\nper_page = 10";
+        let err = Config::from_toml(src, Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`pagination.posts` needs either a `collection` or a `taxonomy`"
+        ));
+        let src = "[navigation]\ncollection = \"blog\"\ntaxonomy = \"posts\"";
+        let err = Config::from_toml(src, Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`navigation` takes either a `collection` or a `taxonomy`"
+        ));
+        let src = "[taxonomies.posts.schema]\nrating = { type = \"integer\", default = \"five\" }";
+        let err = Config::from_toml(src, Path::new("")).err();
+        assert!(matches!(
+            err,
+            Some(ConfigError::Invalid(e)) if e == "`taxonomies.posts.schema.rating.default` must be an integer, found string"
+        ));
+        for (key, dir) in [
+            ("files", ""),
+            ("files", "../src"),
+            ("json_schemas", "/tmp"),
+            ("pages", "."),
+            ("pages", ".."),
+        ] {
+            let src = format!("[naming]\n{} = \"{}\"", key, dir);
+            let err = Config::from_toml(&src, Path::new("")).err();
+            let expected = format!(
+                "`naming.{}` must be a relative path inside the output directory, found `{}`",
+                key, dir
+            );
+            assert!(matches!(err, Some(ConfigError::Invalid(e)) if e == expected));
+        }
+        let src = "[naming]\nfiles = \"modules/files\"";
+        let config = Config::from_toml(src, Path::new("")).unwrap();
+        assert_eq!(config.naming.files, "modules/files");
+    }
+}
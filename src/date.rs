@@ -0,0 +1,180 @@
+/// Seconds since the Unix epoch. Dates without a time are midnight UTC, and
+/// timestamps without an offset are taken to be UTC as well.
+pub type Timestamp = i64;
+
+/// The current time from the system clock.
+pub fn now() -> Timestamp {
+    match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as Timestamp,
+        Err(e) => -(e.duration().as_secs() as Timestamp),
+    }
+}
+
+/// Parses an ISO 8601 date like `2022-08-22` or a timestamp like
+/// `2022-08-22T10:30:00+02:00`. Fractional seconds are accepted and ignored.
+pub fn parse(src: &str) -> Option<Timestamp> {
+    let bytes = src.as_bytes();
+    let year = digits(bytes, 0, 4)?;
+    let month = digits(bytes, 5, 2)?;
+    let day = digits(bytes, 8, 2)?;
+    if bytes.get(4) != Some(&b'-') || bytes.get(7) != Some(&b'-') {
+        return None;
+    }
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let date = days_from_civil(year, month, day) * 86400;
+    let rest = &bytes[10..];
+    if rest.is_empty() {
+        return Some(date);
+    }
+    if !matches!(rest[0], b'T' | b't' | b' ') {
+        return None;
+    }
+    let hour = digits(rest, 1, 2)?;
+    let minute = digits(rest, 4, 2)?;
+    if rest.get(3) != Some(&b':') || hour > 23 || minute > 59 {
+        return None;
+    }
+    let mut idx = 6;
+    let mut second = 0;
+    if rest.get(idx) == Some(&b':') {
+        second = digits(rest, idx + 1, 2)?;
+        if second > 59 {
+            return None;
+        }
+        idx += 3;
+        if rest.get(idx) == Some(&b'.') {
+            idx += 1;
+            let start = idx;
+            while rest.get(idx).is_some_and(u8::is_ascii_digit) {
+                idx += 1;
+            }
+            if idx == start {
+                return None;
+            }
+        }
+    }
+    let time = hour * 3600 + minute * 60 + second;
+    let offset = match &rest[idx..] {
+        [] | [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), offset @ ..] => {
+            let hours = digits(offset, 0, 2)?;
+            let minutes = match offset.len() {
+                2 => 0,
+                4 => digits(offset, 2, 2)?,
+                5 if offset[2] == b':' => digits(offset, 3, 2)?,
+                _ => return None,
+            };
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+    Some(date + time - offset)
+}
+
+/// Writes `timestamp` the way JavaScript's `Date.prototype.toISOString` does,
+/// like `2022-08-22T10:30:00.000Z`, so strings sort like the dates.
+pub fn write_iso<W: std::io::Write>(w: &mut W, timestamp: Timestamp) -> std::io::Result<()> {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86400));
+    let time = timestamp.rem_euclid(86400);
+    w.write_fmt(format_args!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    ))
+}
+
+/// Reads `len` ASCII digits starting at `start`.
+fn digits(bytes: &[u8], start: usize, len: usize) -> Option<i64> {
+    let slice = bytes.get(start..start + len)?;
+    slice.iter().try_fold(0, |n, b| {
+        b.is_ascii_digit().then(|| n * 10 + (b - b'0') as i64)
+    })
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = (month + 9) % 12;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, write_iso};
+
+    #[test]
+    fn parses_dates_and_timestamps() {
+        assert_eq!(parse("1970-01-01"), Some(0));
+        assert_eq!(parse("2022-08-22"), Some(1661126400));
+        assert_eq!(parse("2024-02-29"), Some(1709164800));
+        assert_eq!(parse("2022-08-22T10:30:00Z"), Some(1661164200));
+        assert_eq!(parse("2022-08-22 10:30"), Some(1661164200));
+        assert_eq!(parse("2022-08-22T12:30:00.250+02:00"), Some(1661164200));
+        assert_eq!(parse("2022-08-22T05:30:00-0500"), Some(1661164200));
+        assert_eq!(parse("1969-12-31T23:59:59Z"), Some(-1));
+        for invalid in [
+            "2022",
+            "2022-8-22",
+            "2023-02-29",
+            "2022-13-01",
+            "2022-08-22T25:00",
+            "2022-08-22T10:30:00+2",
+            "2022-08-22 and more",
+        ] {
+            assert_eq!(parse(invalid), None, "{}", invalid);
+        }
+    }
+    #[test]
+    fn writes_iso_strings() {
+        let iso = |src: &str| {
+            let mut out = Vec::new();
+            write_iso(&mut out, parse(src).unwrap()).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(iso("2022-08-22"), "2022-08-22T00:00:00.000Z");
+        assert_eq!(iso("2024-02-29T01:02:03+02:00"), "2024-02-28T23:02:03.000Z");
+        assert_eq!(iso("1969-12-31T23:59:59Z"), "1969-12-31T23:59:59.000Z");
+    }
+}
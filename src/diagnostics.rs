@@ -0,0 +1,228 @@
+use std::{fmt::Write, ops::Range, path::PathBuf, time::Duration};
+
+use crate::{
+    types::{Content, Token},
+    yaml::YamlError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    /// Content that builds, but probably not the way the author intended.
+    Warning,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Error => write!(f, "error"),
+            Level::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem in a content file. `span` is a byte range into the whole file,
+/// frontmatter delimiters included, so it can be shown with its source line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub path: String,
+    pub message: String,
+    pub span: Option<Range<usize>>,
+    /// The job that reported it, filled in by the thread pool.
+    pub job: Option<&'static str>,
+}
+
+impl Diagnostic {
+    pub fn new<P: Into<String>, M: Into<String>>(path: P, message: M) -> Self {
+        Self {
+            level: Level::Error,
+            path: path.into(),
+            message: message.into(),
+            span: None,
+            job: None,
+        }
+    }
+    pub fn warning<P: Into<String>, M: Into<String>>(path: P, message: M) -> Self {
+        Self {
+            level: Level::Warning,
+            ..Self::new(path, message)
+        }
+    }
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+    pub fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+    /// Points at `span`, a range into the frontmatter of `token`.
+    pub fn in_frontmatter<M: Into<String>>(
+        content: &Content,
+        token: &Token,
+        message: M,
+        span: Range<usize>,
+    ) -> Self {
+        let offset = content.frontmatter_offset(token);
+        Self::new(content.path(token), message).with_span(span.start + offset..span.end + offset)
+    }
+    pub fn from_yaml(content: &Content, token: &Token, error: &YamlError) -> Self {
+        Self::in_frontmatter(
+            content,
+            token,
+            format!("invalid frontmatter: {}", error),
+            error.span.clone(),
+        )
+    }
+    /// One based line and column of the start of the span.
+    pub fn location(&self, source: &str) -> Option<(usize, usize)> {
+        let start = self.span.as_ref()?.start.min(source.len());
+        let before = source.get(..start)?;
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Some((line, before[line_start..].chars().count() + 1))
+    }
+    /// Formats the diagnostic like rustc, with the offending line and a caret
+    /// under the span when the source is available.
+    pub fn render(&self, source: Option<&str>) -> String {
+        let mut out = match self.job {
+            Some(job) => format!("{}[{}]: {}\n", self.level, job, self.message),
+            None => format!("{}: {}\n", self.level, self.message),
+        };
+        let located = match (&self.span, source) {
+            (Some(span), Some(source)) => self.location(source).map(|l| (span, source, l)),
+            _ => None,
+        };
+        let Some((span, source, (line, column))) = located else {
+            let _ = write!(out, " --> {}", self.path);
+            return out;
+        };
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        let gutter = line.to_string().len();
+        // Spans past the end of the line are cut off, empty ones still get a caret.
+        let width = source
+            .get(span.start.min(source.len())..span.end.min(source.len()))
+            .map(|s| s.lines().next().unwrap_or_default().chars().count())
+            .unwrap_or_default()
+            .max(1);
+        let _ = writeln!(
+            out,
+            "{:gutter$}--> {}:{}:{}",
+            "",
+            self.path,
+            line,
+            column,
+            gutter = gutter
+        );
+        let _ = writeln!(out, "{:gutter$} |", "", gutter = gutter);
+        let _ = writeln!(out, "{} | {}", line, text.trim_end());
+        let _ = write!(
+            out,
+            "{:gutter$} | {:column$}{}",
+            "",
+            "",
+            "^".repeat(width),
+            gutter = gutter,
+            column = column - 1
+        );
+        out
+    }
+}
+
+/// What a single job did.
+#[derive(Debug)]
+pub struct JobSummary {
+    pub name: &'static str,
+    pub elapsed: Duration,
+    /// Files whose contents changed.
+    pub written: Vec<PathBuf>,
+}
+
+/// Everything reported by one build or check.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub diagnostics: Vec<Diagnostic>,
+    pub jobs: Vec<JobSummary>,
+    pub elapsed: Duration,
+}
+
+impl Report {
+    pub fn errors(&self) -> usize {
+        self.count(Level::Error)
+    }
+    pub fn warnings(&self) -> usize {
+        self.count(Level::Warning)
+    }
+    fn count(&self, level: Level) -> usize {
+        self.diagnostics.iter().filter(|d| d.level == level).count()
+    }
+    /// Whether the run failed, counting warnings as errors with `deny_warnings`.
+    pub fn failed(&self, deny_warnings: bool) -> bool {
+        self.errors() > 0 || (deny_warnings && self.warnings() > 0)
+    }
+    /// Prints every diagnostic with the source of the file it points into,
+    /// followed by the totals.
+    pub fn print(&self, content: &Content) {
+        for diagnostic in self.diagnostics.iter() {
+            let source = content.find(&diagnostic.path).map(|t| content.source(t));
+            println!("{}\n", diagnostic.render(source));
+        }
+        let (errors, warnings) = (self.errors(), self.warnings());
+        if errors + warnings > 0 {
+            println!(
+                "{} {}, {} {}",
+                errors,
+                plural(errors, "error"),
+                warnings,
+                plural(warnings, "warning")
+            );
+        }
+    }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        word.to_owned()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Diagnostic;
+    use crate::types::Content;
+
+    #[test]
+    fn points_at_frontmatter_errors() {
+        let mut content = Content::new();
+        let source = "---\ntitle: Hello\ndate: \"2022\n---\nBody";
+        content.push_file("post.md", source);
+        let token = &content.tokens()[0];
+        let error = content.frontmatter(token).unwrap_err();
+        let diagnostic = Diagnostic::from_yaml(&content, token, &error);
+        assert_eq!(diagnostic.location(source), Some((3, 7)));
+        assert_eq!(
+            diagnostic.render(Some(source)),
+            [
+                "error: invalid frontmatter: expected `\"`",
+                " --> post.md:3:7",
+                "  |",
+                "3 | date: \"2022",
+                "  |       ^^^^^",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            Diagnostic::new("post.md", "broken").render(None),
+            "error: broken\n --> post.md"
+        );
+    }
+}
@@ -2,9 +2,11 @@
 use pulldown_cmark::escape::{escape_href, escape_html};
 use pulldown_cmark::{Alignment, CowStr};
 
-use crate::utils::html_tag;
-use crate::{imports::Imports, types::Config};
-use std::collections::HashMap;
+use crate::config::AnchorPlacement;
+use crate::utils::{html_tag, slugify};
+use crate::yaml::Yaml;
+use crate::{config::Config, imports::Imports};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Write as _};
 use std::iter::Peekable;
 // import without risk of name clashing
@@ -33,6 +35,126 @@ impl Display for Content {
 #[derive(Debug)]
 pub struct ContentVec {
     inner: Vec<Content>,
+    pub stats: TextStats,
+    pub excerpt: Excerpt,
+    pub headings: Vec<Heading>,
+    /// The headings within the configured depths, nested by level.
+    pub toc: Vec<TocEntry>,
+}
+
+impl ContentVec {
+    pub fn write_headings<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(b"[")?;
+        for (idx, heading) in self.headings.iter().enumerate() {
+            w.write_all(if idx > 0 { b", " } else { b" " })?;
+            heading.write_js(w)?;
+            w.write_all(b" }")?;
+        }
+        w.write_all(if self.headings.is_empty() {
+            b"]"
+        } else {
+            b" ]"
+        })
+    }
+    pub fn write_toc<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        fn write_entries<W: std::io::Write>(
+            w: &mut W,
+            headings: &[Heading],
+            entries: &[TocEntry],
+        ) -> std::io::Result<()> {
+            w.write_all(b"[")?;
+            for (idx, entry) in entries.iter().enumerate() {
+                w.write_all(if idx > 0 { b", " } else { b" " })?;
+                headings[entry.heading].write_js(w)?;
+                w.write_all(b", children: ")?;
+                write_entries(w, headings, &entry.children)?;
+                w.write_all(b" }")?;
+            }
+            w.write_all(if entries.is_empty() { b"]" } else { b" ]" })
+        }
+        write_entries(w, &self.headings, &self.toc)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    /// Plain text, without any markup.
+    pub text: String,
+    pub id: String,
+}
+
+impl Heading {
+    /// Writes the fields, leaving the object open for more.
+    fn write_js<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_fmt(format_args!("{{ level: {}, text: ", self.level))?;
+        crate::json::write_str(w, &self.text)?;
+        w.write_all(b", id: ")?;
+        crate::json::write_str(w, &self.id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// Index into `ContentVec::headings`.
+    pub heading: usize,
+    pub children: Vec<TocEntry>,
+}
+
+/// Nests the headings from `min` to `max` under the closest heading above
+/// them with a lower level.
+fn toc(headings: &[Heading], min: usize, max: usize) -> Vec<TocEntry> {
+    let mut roots = Vec::new();
+    // The entries from the last root down to the last heading.
+    let mut open: Vec<TocEntry> = Vec::new();
+    fn close(open: &mut Vec<TocEntry>, roots: &mut Vec<TocEntry>) {
+        if let Some(entry) = open.pop() {
+            match open.last_mut() {
+                Some(parent) => parent.children.push(entry),
+                None => roots.push(entry),
+            }
+        }
+    }
+    for (idx, heading) in headings.iter().enumerate() {
+        if heading.level < min || heading.level > max {
+            continue;
+        }
+        while open
+            .last()
+            .is_some_and(|e| headings[e.heading].level >= heading.level)
+        {
+            close(&mut open, &mut roots);
+        }
+        open.push(TocEntry {
+            heading: idx,
+            children: Vec::new(),
+        });
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
+    roots
+}
+
+/// The start of a page, for listings that should not ship all of it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Excerpt {
+    pub html: String,
+    pub text: String,
+}
+
+/// Ends the excerpt when it sits between blocks.
+const MORE_MARKER: &str = "<!-- more -->";
+
+/// Counts of the readable text of a page. Code, raw HTML and MDX components
+/// are left out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextStats {
+    pub words: usize,
+    /// Characters of the rendered text, line breaks counting as spaces.
+    pub characters: usize,
+    /// Minutes, rounded up.
+    pub reading_time: usize,
 }
 
 impl Display for ContentVec {
@@ -63,12 +185,35 @@ pub struct Markdown<'a> {
     table_alignments: Vec<Alignment>,
     table_cell_index: usize,
     numbers: HashMap<CowStr<'a>, usize>,
+    stats: TextStats,
+    in_word: bool,
+    in_code_block: bool,
+    /// Plain text of the page, one line per block.
+    text: String,
+    /// Blocks currently open.
+    depth: usize,
+    excerpt_paragraphs: usize,
+    paragraphs: usize,
+    /// Offsets into the HTML and the text where the first paragraph starts,
+    /// where the configured number of paragraphs ends and where the marker is.
+    excerpt_start: Option<(usize, usize)>,
+    paragraphs_end: Option<(usize, usize)>,
+    marker: Option<(usize, usize)>,
+    headings: Vec<Heading>,
+    in_heading: bool,
+    /// The chunk of `content` the open heading is written to, and where its
+    /// attributes and its text start in it. The chunk is the HTML buffer
+    /// until it is flushed for a component.
+    heading_at: Option<(usize, usize, usize)>,
+    /// Heading ids already used on the page.
+    ids: HashSet<String>,
+    /// Ids the author gave headings anywhere on the page.
+    reserved: HashSet<String>,
 }
 
 impl<'a> Markdown<'a> {
     pub fn new(config: Arc<Config>) -> Self {
         Self {
-            config,
             content: Vec::default(),
             html_buffer: String::default(),
             component_buffer: Vec::default(),
@@ -76,12 +221,42 @@ impl<'a> Markdown<'a> {
             table_alignments: vec![],
             table_cell_index: 0,
             numbers: HashMap::new(),
+            stats: TextStats::default(),
+            in_word: false,
+            in_code_block: false,
+            text: String::new(),
+            depth: 0,
+            excerpt_paragraphs: config.excerpt_paragraphs,
+            paragraphs: 0,
+            excerpt_start: None,
+            paragraphs_end: None,
+            marker: None,
+            headings: Vec::new(),
+            in_heading: false,
+            heading_at: None,
+            ids: HashSet::new(),
+            reserved: HashSet::new(),
+            config,
         }
     }
     pub fn reset(&mut self) {
         self.content.clear();
         self.html_buffer.clear();
         self.component_buffer.clear();
+        self.stats = TextStats::default();
+        self.in_word = false;
+        self.in_code_block = false;
+        self.text.clear();
+        self.depth = 0;
+        self.paragraphs = 0;
+        self.excerpt_start = None;
+        self.paragraphs_end = None;
+        self.marker = None;
+        self.headings.clear();
+        self.in_heading = false;
+        self.heading_at = None;
+        self.ids.clear();
+        self.reserved.clear();
     }
     pub fn content(&mut self) -> Vec<Content> {
         let content = self.content.drain(..).collect();
@@ -93,9 +268,159 @@ impl<'a> Markdown<'a> {
     pub fn dump_html(&mut self) {
         if !self.html_buffer.is_empty() {
             let html = self.html_buffer.drain(..).collect();
-            self.content.push(Content::Html(html))
+            self.content.push(Content::Html(html));
+        }
+    }
+    /// Counts `text` unless it is code. Text events may split a word, so it
+    /// only ends at whitespace or at the edge of a block. Punctuation on its
+    /// own is not a word.
+    fn count_text(&mut self, text: &str) {
+        if self.in_code_block {
+            return;
+        }
+        self.text.push_str(text);
+        if self.in_heading {
+            if let Some(heading) = self.headings.last_mut() {
+                heading.text.push_str(text);
+            }
+        }
+        for char in text.chars() {
+            self.stats.characters += 1;
+            if char.is_whitespace() {
+                self.in_word = false;
+            } else if char.is_alphanumeric() && !self.in_word {
+                self.stats.words += 1;
+                self.in_word = true;
+            }
+        }
+    }
+    /// The id of the heading being closed: the one the author gave it or a
+    /// slug of its text, numbered if an earlier heading already took it.
+    /// Slugs also stay clear of the ids the author gave later headings.
+    fn heading_id(&mut self) -> String {
+        let Some(heading) = self.headings.last_mut() else {
+            return String::new();
+        };
+        heading.text = heading.text.trim().to_owned();
+        let explicit = !heading.id.is_empty();
+        let base = if explicit {
+            heading.id.clone()
+        } else {
+            slugify(&heading.text)
+        };
+        let mut id = base.clone();
+        let mut n = 0;
+        while self.ids.contains(&id) || ((n > 0 || !explicit) && self.reserved.contains(&id)) {
+            n += 1;
+            id = format!("{}-{}", base, n);
+        }
+        heading.id = id;
+        self.ids.insert(heading.id.clone());
+        heading.id.clone()
+    }
+    /// Collects the `{#id}`s of every heading in `src` before any is written.
+    fn reserve_ids(&mut self, src: &str) {
+        let options = self.config.markdown.parser_options();
+        if !options.contains(pulldown_cmark::Options::ENABLE_HEADING_ATTRIBUTES) {
+            return;
+        }
+        for event in pulldown_cmark::Parser::new_ext(src, options) {
+            if let pulldown_cmark::Event::Start(pulldown_cmark::Tag::Heading(_, Some(id), _)) =
+                event
+            {
+                self.reserved.insert(id.to_owned());
+            }
+        }
+    }
+    /// Inline code is part of the text but not counted.
+    fn push_code_text(&mut self, text: &str) {
+        self.text.push_str(text);
+        if self.in_heading {
+            if let Some(heading) = self.headings.last_mut() {
+                heading.text.push_str(text);
+            }
+        }
+    }
+    fn count_break(&mut self) {
+        self.text.push(' ');
+        if self.in_word {
+            self.stats.characters += 1;
+        }
+        self.in_word = false;
+    }
+    /// Bytes of HTML written so far, components left out.
+    fn html_len(&self) -> usize {
+        let written: usize = self
+            .content
+            .iter()
+            .map(|c| match c {
+                Content::Html(html) => html.len(),
+                Content::Component(_) => 0,
+            })
+            .sum();
+        written + self.html_buffer.len()
+    }
+    fn check_marker(&mut self, html: &str) {
+        if self.depth == 0 && self.marker.is_none() && html.trim() == MORE_MARKER {
+            self.marker = Some((self.html_len(), self.text.len()));
+        }
+    }
+    /// Everything up to the marker, or the first paragraphs without one.
+    fn excerpt(&self) -> Excerpt {
+        let mut html = String::with_capacity(self.html_len());
+        for content in self.content.iter() {
+            if let Content::Html(part) = content {
+                html.push_str(part);
+            }
+        }
+        let all = (html.len(), self.text.len());
+        let (start, end) = match (self.marker, self.excerpt_start) {
+            (Some(end), _) => ((0, 0), end),
+            (None, Some(start)) => (start, self.paragraphs_end.unwrap_or(all)),
+            (None, None) => return Excerpt::default(),
+        };
+        Excerpt {
+            html: html[start.0..end.0].trim().to_owned(),
+            text: self.text[start.1..end.1].trim().to_owned(),
         }
     }
+    fn finish(&mut self) -> ContentVec {
+        self.dump_html();
+        let mut stats = self.stats;
+        stats.reading_time = stats.words.div_ceil(self.config.words_per_minute.max(1));
+        let excerpt = self.excerpt();
+        let headings = std::mem::take(&mut self.headings);
+        let toc = toc(
+            &headings,
+            self.config.toc.min_depth,
+            self.config.toc.max_depth,
+        );
+        let content = self.content();
+        self.reset();
+        ContentVec {
+            inner: content,
+            stats,
+            excerpt,
+            headings,
+            toc,
+        }
+    }
+    /// Renders the `excerpt` or `summary` given in the frontmatter, if any.
+    pub fn frontmatter_excerpt(
+        config: Arc<Config>,
+        frontmatter: &Yaml,
+    ) -> std::io::Result<Option<Excerpt>> {
+        let Some(src) = frontmatter
+            .string("excerpt")
+            .or_else(|| frontmatter.string("summary"))
+        else {
+            return Ok(None);
+        };
+        let mut markdown = Markdown::new(config);
+        // All of it, the author already picked what to show.
+        markdown.excerpt_paragraphs = usize::MAX;
+        Ok(Some(markdown.write_md(src)?.excerpt))
+    }
     pub fn dump_component(&mut self) {
         if !self.component_buffer.is_empty() {
             let component = self.component_buffer.drain(..).collect();
@@ -103,29 +428,49 @@ impl<'a> Markdown<'a> {
         }
     }
     pub fn start_tag(&mut self, tag: pulldown_cmark::Tag<'a>) -> std::io::Result<()> {
+        if !is_inline(&tag) {
+            self.in_word = false;
+        }
+        if let pulldown_cmark::Tag::CodeBlock(_) = tag {
+            self.in_code_block = true;
+        }
+        if let pulldown_cmark::Tag::Paragraph = tag {
+            if self.depth == 0 && self.excerpt_start.is_none() {
+                self.excerpt_start = Some((self.html_len(), self.text.len()));
+            }
+        }
+        if !is_inline(&tag) {
+            self.depth += 1;
+        }
+        if let pulldown_cmark::Tag::Heading(level, id, _) = &tag {
+            self.in_heading = true;
+            self.headings.push(Heading {
+                level: *level as usize,
+                text: String::new(),
+                id: id.map(String::from).unwrap_or_default(),
+            });
+        }
         match tag {
             pulldown_cmark::Tag::Paragraph => {
                 self.push_html_str("<p>");
             }
-            pulldown_cmark::Tag::Heading(lvl, id, classes) => {
+            pulldown_cmark::Tag::Heading(lvl, _, classes) => {
                 self.push_html_str("<");
                 self.push_html_str(&lvl.to_string());
-                if let Some(id) = id {
-                    self.push_html_str(" id=\"");
-                    self.push_html_str(id);
-                    self.html_buffer.push('"');
-                }
+                // The id is only known once the text was read.
+                let attributes = self.html_buffer.len();
                 let mut classes = classes.iter();
                 if let Some(class) = classes.next() {
                     self.push_html_str(" class=\"");
-                    self.push_html_str(class);
+                    escape_html(&mut self.html_buffer, class)?;
                     for class in classes {
-                        self.push_html_str(class);
                         self.html_buffer.push(' ');
+                        escape_html(&mut self.html_buffer, class)?;
                     }
                     self.html_buffer.push('"');
                 }
-                self.html_buffer.push('>')
+                self.html_buffer.push('>');
+                self.heading_at = Some((self.content.len(), attributes, self.html_buffer.len()));
             }
             pulldown_cmark::Tag::BlockQuote => self.push_html_str("<blockquote>"),
             pulldown_cmark::Tag::CodeBlock(info) => match info {
@@ -152,7 +497,7 @@ impl<'a> Markdown<'a> {
             pulldown_cmark::Tag::FootnoteDefinition(name) => {
                 self.html_buffer
                     .push_str("<div class=\"footnote-definition\" id=\"");
-                escape_html(&mut self.html_buffer, &*name)?;
+                escape_html(&mut self.html_buffer, &name)?;
                 self.html_buffer
                     .push_str("\"><sup class=\"footnote-definition-label\">");
                 let len = self.numbers.len() + 1;
@@ -227,15 +572,55 @@ impl<'a> Markdown<'a> {
         Ok(())
     }
     pub fn end_tag(&mut self, tag: pulldown_cmark::Tag) -> std::io::Result<()> {
+        let block = !is_inline(&tag);
+        let paragraph = matches!(tag, pulldown_cmark::Tag::Paragraph);
+        if let pulldown_cmark::Tag::Heading(..) = tag {
+            self.in_heading = false;
+        }
+        if block {
+            self.in_word = false;
+            self.depth = self.depth.saturating_sub(1);
+            if !self.text.is_empty() && !self.text.ends_with('\n') {
+                self.text.push('\n');
+            }
+        }
         match tag {
             pulldown_cmark::Tag::Paragraph => self.push_html_str("</p>"),
             pulldown_cmark::Tag::Heading(lvl, _, _) => {
+                let id = self.heading_id();
+                let mut anchor = String::new();
+                if self.config.anchors.placement.is_some() {
+                    anchor.push_str("<a class=\"");
+                    escape_html(&mut anchor, &self.config.anchors.class)?;
+                    anchor.push_str("\" href=\"#");
+                    escape_href(&mut anchor, &id)?;
+                    anchor.push_str("\" aria-hidden=\"true\">#</a>");
+                }
+                if let Some((chunk, attributes, text)) = self.heading_at.take() {
+                    let html = match self.content.get_mut(chunk) {
+                        Some(Content::Html(html)) => html,
+                        _ => &mut self.html_buffer,
+                    };
+                    if self.config.anchors.placement == Some(AnchorPlacement::Before) {
+                        html.insert_str(text, &anchor);
+                    }
+                    let mut attribute = String::from(" id=\"");
+                    escape_html(&mut attribute, &id)?;
+                    attribute.push('"');
+                    html.insert_str(attributes, &attribute);
+                }
+                if self.config.anchors.placement == Some(AnchorPlacement::After) {
+                    self.push_html_str(&anchor);
+                }
                 self.push_html_str("</");
                 let _ = write!(self.html_buffer, "{}", lvl);
                 self.html_buffer.push('>')
             }
             pulldown_cmark::Tag::BlockQuote => self.push_html_str("</blockquote>"),
-            pulldown_cmark::Tag::CodeBlock(_) => self.push_html_str("</code></pre>"),
+            pulldown_cmark::Tag::CodeBlock(_) => {
+                self.in_code_block = false;
+                self.push_html_str("</code></pre>")
+            }
             pulldown_cmark::Tag::List(Some(_)) => self.push_html_str("</ol>"),
             pulldown_cmark::Tag::List(None) => self.push_html_str("</ul>"),
             pulldown_cmark::Tag::Item => self.push_html_str("</li>"),
@@ -263,17 +648,29 @@ impl<'a> Markdown<'a> {
             pulldown_cmark::Tag::Link(_, _, _) => self.push_html_str("</a>"),
             pulldown_cmark::Tag::Image(_, _, _) => {}
         }
+        if paragraph && self.depth == 0 {
+            self.paragraphs += 1;
+            if self.paragraphs == self.excerpt_paragraphs {
+                self.paragraphs_end = Some((self.html_len(), self.text.len()));
+            }
+        }
         Ok(())
     }
     pub fn write_mdx(&'a mut self, src: &'a str, imports: &Imports) -> std::io::Result<ContentVec> {
+        let options = self.config.markdown.parser_options();
+        self.reserve_ids(src);
         let mut parser: Peekable<pulldown_cmark::Parser<'a, 'a>> =
-            pulldown_cmark::Parser::new(src).into_iter().peekable();
+            pulldown_cmark::Parser::new_ext(src, options).peekable();
         while let Some(event) = parser.next() {
             match event {
                 pulldown_cmark::Event::Start(tag) => self.start_tag(tag)?,
                 pulldown_cmark::Event::End(tag) => self.end_tag(tag)?,
-                pulldown_cmark::Event::Text(string) => self.push_html_str(&string),
+                pulldown_cmark::Event::Text(string) => {
+                    self.count_text(&string);
+                    self.push_html_str(&string)
+                }
                 pulldown_cmark::Event::Code(text) => {
+                    self.push_code_text(&text);
                     self.push_html_str("<code>");
                     escape_html(&mut self.html_buffer, &text)?;
                     self.push_html_str("</code>");
@@ -300,6 +697,7 @@ impl<'a> Markdown<'a> {
                         }
                         self.dump_component();
                     } else {
+                        self.check_marker(&tag);
                         self.push_html_str(&tag)
                     }
                 }
@@ -313,8 +711,14 @@ impl<'a> Markdown<'a> {
                     self.push_html_str(&number.to_string());
                     self.push_html_str("</a></sup>");
                 }
-                pulldown_cmark::Event::SoftBreak => {}
-                pulldown_cmark::Event::HardBreak => self.push_html_str("<br/>"),
+                pulldown_cmark::Event::SoftBreak => {
+                    self.count_break();
+                    self.push_html_str("\n")
+                }
+                pulldown_cmark::Event::HardBreak => {
+                    self.count_break();
+                    self.push_html_str("<br/>")
+                }
                 pulldown_cmark::Event::Rule => self.push_html_str("<hr/>"),
                 pulldown_cmark::Event::TaskListMarker(true) => self
                     .html_buffer
@@ -324,24 +728,29 @@ impl<'a> Markdown<'a> {
                     .push_str("<input disabled=\"\" type=\"checkbox\" />"),
             }
         }
-        self.dump_html();
-        let content = self.content();
-        self.reset();
-        Ok(ContentVec { inner: content })
+        Ok(self.finish())
     }
     pub fn write_md(&'a mut self, src: &'a str) -> std::io::Result<ContentVec> {
-        let parser = pulldown_cmark::Parser::new(src);
+        self.reserve_ids(src);
+        let parser = pulldown_cmark::Parser::new_ext(src, self.config.markdown.parser_options());
         for event in parser {
             match event {
                 pulldown_cmark::Event::Start(tag) => self.start_tag(tag)?,
                 pulldown_cmark::Event::End(tag) => self.end_tag(tag)?,
-                pulldown_cmark::Event::Text(string) => self.push_html_str(&string),
+                pulldown_cmark::Event::Text(string) => {
+                    self.count_text(&string);
+                    self.push_html_str(&string)
+                }
                 pulldown_cmark::Event::Code(text) => {
+                    self.push_code_text(&text);
                     self.push_html_str("<code>");
                     escape_html(&mut self.html_buffer, &text)?;
                     self.push_html_str("</code>");
                 }
-                pulldown_cmark::Event::Html(html) => self.push_html_str(&html),
+                pulldown_cmark::Event::Html(html) => {
+                    self.check_marker(&html);
+                    self.push_html_str(&html)
+                }
                 pulldown_cmark::Event::FootnoteReference(name) => {
                     let len = self.numbers.len() + 1;
                     self.html_buffer
@@ -352,8 +761,14 @@ impl<'a> Markdown<'a> {
                     self.push_html_str(&number.to_string());
                     self.push_html_str("</a></sup>");
                 }
-                pulldown_cmark::Event::SoftBreak => {}
-                pulldown_cmark::Event::HardBreak => self.push_html_str("<br/>"),
+                pulldown_cmark::Event::SoftBreak => {
+                    self.count_break();
+                    self.push_html_str("\n")
+                }
+                pulldown_cmark::Event::HardBreak => {
+                    self.count_break();
+                    self.push_html_str("<br/>")
+                }
                 pulldown_cmark::Event::Rule => self.push_html_str("<hr/>"),
                 pulldown_cmark::Event::TaskListMarker(true) => self
                     .html_buffer
@@ -363,18 +778,28 @@ impl<'a> Markdown<'a> {
                     .push_str("<input disabled=\"\" type=\"checkbox\" />"),
             }
         }
-        self.dump_html();
-        let content = self.content();
-        self.reset();
-        Ok(ContentVec { inner: content })
+        Ok(self.finish())
     }
 }
 
+/// Tags that sit inside a block of text.
+fn is_inline(tag: &pulldown_cmark::Tag) -> bool {
+    matches!(
+        tag,
+        pulldown_cmark::Tag::Emphasis
+            | pulldown_cmark::Tag::Strong
+            | pulldown_cmark::Tag::Strikethrough
+            | pulldown_cmark::Tag::Link(..)
+            | pulldown_cmark::Tag::Image(..)
+    )
+}
+
 #[cfg(test)]
 mod test {
     use std::{path::PathBuf, sync::Arc};
 
-    use crate::types::Config;
+    use crate::config::{AnchorPlacement, Config};
+    use crate::imports::Imports;
 
     use super::Markdown;
 
@@ -386,4 +811,79 @@ mod test {
         let content = markdown.write_md(src).unwrap();
         println!("{}", content);
     }
+    #[test]
+    fn counts_text() {
+        let src = "# Some *em*phasis\n\nA line\nwrapped, with `code`.\n\n```rust\nlet skipped = 1;\n```\n";
+        let mut config = Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new());
+        config.words_per_minute = 3;
+        let mut markdown = Markdown::new(Arc::new(config));
+        let stats = markdown.write_md(src).unwrap().stats;
+        assert_eq!(stats.words, 6);
+        assert_eq!(stats.characters, 35);
+        assert_eq!(stats.reading_time, 2);
+    }
+    #[test]
+    fn takes_excerpts() {
+        let config = Arc::new(Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new()));
+        let src = "# Title\n\nFirst *one*.\n\nSecond.\n";
+        let excerpt = Markdown::new(config.clone()).write_md(src).unwrap().excerpt;
+        assert_eq!(excerpt.html, "<p>First <em>one</em>.</p>");
+        assert_eq!(excerpt.text, "First one.");
+        let src = "Intro\nwith `code`.\n\n- a list\n\n<!-- more -->\n\nRest.\n";
+        let excerpt = Markdown::new(config).write_md(src).unwrap().excerpt;
+        assert_eq!(
+            excerpt.html,
+            "<p>Intro\nwith <code>code</code>.</p><ul><li>a list</li></ul>"
+        );
+        assert_eq!(excerpt.text, "Intro with code.\na list");
+    }
+    #[test]
+    fn numbers_heading_ids() {
+        let mut config = Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new());
+        config.anchors.placement = Some(AnchorPlacement::Before);
+        let src = "## Intro\n\n## Intro\n\n## Set {#custom .wide}\n";
+        let content = Markdown::new(Arc::new(config)).write_md(src).unwrap();
+        let ids: Vec<&str> = content.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["intro", "intro-1", "custom"]);
+        // Written as a JavaScript string, so quotes are escaped.
+        let html = content.to_string();
+        assert!(html.contains(
+            r##"<h2 id=\"intro-1\"><a class=\"anchor\" href=\"#intro-1\" aria-hidden=\"true\">#</a>Intro</h2>"##
+        ));
+        assert!(html.contains(r#"<h2 id=\"custom\" class=\"wide\">"#));
+    }
+    #[test]
+    fn keeps_heading_ids_unique() {
+        let mut config = Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new());
+        config.anchors.placement = Some(AnchorPlacement::Before);
+        let config = Arc::new(config);
+        let src = "## Custom\n\n## Other {#custom}\n\n## Twice {#twice}\n\n## Again {#twice}\n";
+        let content = Markdown::new(config.clone()).write_md(src).unwrap();
+        let ids: Vec<&str> = content.headings.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["custom-1", "custom", "twice", "twice-1"]);
+        // The component flushes the heading before its id is known.
+        let mut imports = Imports::default();
+        imports.push_import("Badge");
+        let src = "## New <Badge /> release\n";
+        let content = Markdown::new(config).write_mdx(src, &imports).unwrap();
+        assert_eq!(content.headings[0].id, "new-release");
+        assert!(content.to_string().contains(
+            r##"<h2 id=\"new-release\"><a class=\"anchor\" href=\"#new-release\" aria-hidden=\"true\">#</a>New "##
+        ));
+    }
+    #[test]
+    fn nests_headings() {
+        let config = Arc::new(Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new()));
+        let src = "# Title\n\n## One `a`\n\n### Deep\n\n#### Too deep\n\n## Two\n\n### Under two\n";
+        let content = Markdown::new(config).write_md(src).unwrap();
+        assert_eq!(content.headings.len(), 6);
+        assert_eq!(content.headings[1].text, "One a");
+        let mut toc = Vec::new();
+        content.write_toc(&mut toc).unwrap();
+        assert_eq!(
+            String::from_utf8(toc).unwrap(),
+            "[ { level: 2, text: \"One a\", id: \"one-a\", children: [ { level: 3, text: \"Deep\", id: \"deep\", children: [] } ] }, \
+             { level: 2, text: \"Two\", id: \"two\", children: [ { level: 3, text: \"Under two\", id: \"under-two\", children: [] } ] } ]"
+        );
+    }
 }
@@ -1,4 +1,4 @@
-#![allow(dead_code)]
+#![allow(dead_code, clippy::useless_conversion)]
 #[derive(Default)]
 pub struct Imports<'a> {
     imports: Vec<&'a str>,
@@ -91,6 +91,7 @@ impl<'a> Parser<'a> {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test {
     use super::Parser;
 
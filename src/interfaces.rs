@@ -0,0 +1,480 @@
+use std::io::Write;
+
+use crate::{
+    schema::{FieldType, Literal, Schema},
+    types::Content,
+    yaml::{YamlKind, YamlNode},
+};
+
+/// A TypeScript type, built from the frontmatter of every member of a
+/// collection or taxonomy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    String,
+    Number,
+    Boolean,
+    Undefined,
+    Unknown,
+    Literal(Literal),
+    /// A type imported by the generated module, like `JSXNode`.
+    Named(&'static str),
+    /// An object with unknown keys.
+    Record,
+    Array(Box<Type>),
+    Object(Vec<Property>),
+    Union(Vec<Type>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub name: String,
+    pub ty: Type,
+    pub optional: bool,
+}
+
+impl Property {
+    fn new(name: &str, ty: Type) -> Self {
+        Self {
+            name: name.to_owned(),
+            ty,
+            optional: false,
+        }
+    }
+}
+
+/// Every type seen for a value, unioned.
+#[derive(Debug, Default, Clone)]
+struct Shape {
+    string: bool,
+    number: bool,
+    boolean: bool,
+    date: bool,
+    undefined: bool,
+    list: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+/// The fields of every object seen at one place, with how often each occurred.
+#[derive(Debug, Default, Clone)]
+struct ObjectShape {
+    count: usize,
+    fields: Vec<(String, Shape, usize)>,
+}
+
+impl Shape {
+    /// Adds the value at `nodes[idx]`.
+    fn add(&mut self, nodes: &[YamlNode], src: &str, idx: usize) {
+        match nodes[idx].kind {
+            YamlKind::String => self.string = true,
+            YamlKind::Number => self.number = true,
+            YamlKind::Bool => self.boolean = true,
+            YamlKind::Null => self.undefined = true,
+            YamlKind::Date => self.date = true,
+            YamlKind::Key => {}
+            YamlKind::List => {
+                let items = self.list.get_or_insert_with(Box::default);
+                // Node ids are one based, so children point past the index.
+                for (child, _) in children(nodes, idx + 1) {
+                    items.add(nodes, src, child);
+                }
+            }
+            YamlKind::Object => {
+                self.object
+                    .get_or_insert_with(ObjectShape::default)
+                    .add(nodes, src, idx + 1)
+            }
+        }
+    }
+    fn to_type(&self) -> Type {
+        let mut types = Vec::new();
+        if self.string {
+            types.push(Type::String);
+        }
+        if self.date {
+            types.push(Type::Named("ISODate"));
+        }
+        if self.number {
+            types.push(Type::Number);
+        }
+        if self.boolean {
+            types.push(Type::Boolean);
+        }
+        if let Some(object) = &self.object {
+            types.push(Type::Object(object.properties()));
+        }
+        if let Some(items) = &self.list {
+            types.push(Type::Array(Box::new(items.to_type())));
+        }
+        if self.undefined {
+            types.push(Type::Undefined);
+        }
+        match types.len() {
+            0 => Type::Unknown,
+            1 => types.remove(0),
+            _ => Type::Union(types),
+        }
+    }
+}
+
+impl ObjectShape {
+    /// Adds the object whose keys have `parent` as their parent id.
+    fn add(&mut self, nodes: &[YamlNode], src: &str, parent: usize) {
+        self.count += 1;
+        for (idx, key) in children(nodes, parent) {
+            if key.kind != YamlKind::Key {
+                continue;
+            }
+            let name = unquote(key.slice(src));
+            let pos = match self.fields.iter().position(|(n, _, _)| n == name) {
+                Some(pos) => pos,
+                None => {
+                    self.fields.push((name.to_owned(), Shape::default(), 0));
+                    self.fields.len() - 1
+                }
+            };
+            let (_, shape, count) = &mut self.fields[pos];
+            *count += 1;
+            // Values directly follow their key.
+            if nodes.get(idx + 1).map(|n| n.parent) == Some(idx + 1) {
+                shape.add(nodes, src, idx + 1);
+            }
+        }
+    }
+    /// Fields missing from some objects, or null in some, are optional.
+    fn properties(&self) -> Vec<Property> {
+        self.fields
+            .iter()
+            .map(|(name, shape, count)| {
+                let ty = if shape.undefined {
+                    let defined = Shape {
+                        undefined: false,
+                        ..shape.clone()
+                    };
+                    match defined.to_type() {
+                        Type::Unknown => Type::Undefined,
+                        ty => ty,
+                    }
+                } else {
+                    shape.to_type()
+                };
+                Property {
+                    name: name.clone(),
+                    ty,
+                    optional: *count < self.count || shape.undefined,
+                }
+            })
+            .collect()
+    }
+}
+
+fn children(nodes: &[YamlNode], parent: usize) -> impl Iterator<Item = (usize, &YamlNode)> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(move |(_, n)| n.parent == parent)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .trim_start_matches(['"', '\''])
+        .trim_end_matches(['"', '\''])
+}
+
+/// The properties of the page modules at `members`: the fields every page
+/// has, followed by the frontmatter.
+pub fn properties(content: &Content, members: &[usize], schema: Option<&Schema>) -> Vec<Property> {
+    let mdx = members
+        .iter()
+        .filter_map(|idx| content.tokens().get(*idx))
+        .any(|token| content.path(token).ends_with(".mdx"));
+    let item = if mdx {
+        Type::Union(vec![Type::String, Type::Named("JSXNode")])
+    } else {
+        Type::String
+    };
+    let mut properties = vec![
+        Property::new("_id", Type::String),
+        Property::new("_slug", Type::String),
+        Property::new("_raw", Type::String),
+        Property::new("_directory", Type::String),
+        Property::new("_content", Type::Array(Box::new(item))),
+        Property::new("_draft", Type::Boolean),
+        Property::new("_wordCount", Type::Number),
+        Property::new("_readingTime", Type::Number),
+        Property::new("_characterCount", Type::Number),
+        Property::new(
+            "_excerpt",
+            Type::Object(vec![
+                Property::new("html", Type::String),
+                Property::new("text", Type::String),
+            ]),
+        ),
+        Property::new("_headings", Type::Array(Box::new(heading()))),
+        Property::new("_toc", Type::Array(Box::new(Type::Named("TocEntry")))),
+        Property::new("_prev", Type::Union(vec![link(), Type::Undefined])),
+        Property::new("_next", Type::Union(vec![link(), Type::Undefined])),
+    ];
+    properties.extend(frontmatter(content, members, schema));
+    properties
+}
+
+/// Entries of `_toc`, which nest and so need a name of their own.
+pub const TOC_ENTRY: &str = "export interface TocEntry {
+  level: number;
+  text: string;
+  id: string;
+  children: TocEntry[];
+}
+";
+
+/// Frontmatter dates, written as `Date.prototype.toISOString` would.
+pub const ISO_DATE: &str = "export type ISODate = string & { readonly __brand: \"ISODate\" };
+";
+
+/// An item of `_headings`, `TocEntry` without the children.
+fn heading() -> Type {
+    Type::Object(vec![
+        Property::new("level", Type::Number),
+        Property::new("text", Type::String),
+        Property::new("id", Type::String),
+    ])
+}
+
+/// `_prev` and `_next`, see [`crate::navigation::Link`].
+fn link() -> Type {
+    Type::Object(vec![
+        Property::new("id", Type::String),
+        Property::new("slug", Type::String),
+        Property::new("title", Type::String),
+        Property::new("url", Type::String),
+    ])
+}
+
+/// The frontmatter fields of the pages at `members`, with `schema` taking
+/// precedence over what was inferred.
+pub fn frontmatter(content: &Content, members: &[usize], schema: Option<&Schema>) -> Vec<Property> {
+    let mut shape = ObjectShape::default();
+    for token in members.iter().filter_map(|idx| content.tokens().get(*idx)) {
+        let path = content.path(token);
+        if !(path.ends_with(".md") || path.ends_with(".mdx")) {
+            continue;
+        }
+        match content.frontmatter(token) {
+            Ok(yaml) => shape.add(yaml.inner(), yaml.src(), 0),
+            // Broken frontmatter is written without any fields.
+            Err(_) => shape.count += 1,
+        }
+    }
+    let mut properties = Vec::new();
+    let inferred = shape.properties();
+    let fields = schema.map(|s| s.fields.as_slice()).unwrap_or_default();
+    for property in inferred.iter() {
+        if !fields.iter().any(|f| f.name == property.name) {
+            properties.push(property.clone());
+        }
+    }
+    // Declared fields go last, in the order of the schema.
+    for field in fields {
+        let inferred = inferred.iter().find(|p| p.name == field.name);
+        let ty = if !field.allowed.is_empty() {
+            let mut literals: Vec<Type> =
+                field.allowed.iter().cloned().map(Type::Literal).collect();
+            if literals.len() == 1 {
+                literals.remove(0)
+            } else {
+                Type::Union(literals)
+            }
+        } else {
+            match field.kind {
+                FieldType::String => Type::String,
+                FieldType::Number | FieldType::Integer => Type::Number,
+                FieldType::Boolean => Type::Boolean,
+                FieldType::Date => Type::Named("ISODate"),
+                FieldType::List => match inferred.map(|p| &p.ty) {
+                    Some(Type::Array(items)) => Type::Array(items.clone()),
+                    _ => Type::Array(Box::new(Type::Unknown)),
+                },
+                FieldType::Object => match inferred.map(|p| &p.ty) {
+                    Some(Type::Object(props)) => Type::Object(props.clone()),
+                    _ => Type::Record,
+                },
+            }
+        };
+        properties.push(Property {
+            name: field.name.clone(),
+            ty,
+            optional: !field.required && field.default.is_none(),
+        });
+    }
+    properties
+}
+
+/// Whether any of `properties` refers to `name`, so it needs to be imported.
+pub fn uses(properties: &[Property], name: &str) -> bool {
+    fn uses_type(ty: &Type, name: &str) -> bool {
+        match ty {
+            Type::Named(n) => *n == name,
+            Type::Array(items) => uses_type(items, name),
+            Type::Object(props) => uses(props, name),
+            Type::Union(types) => types.iter().any(|t| uses_type(t, name)),
+            _ => false,
+        }
+    }
+    properties.iter().any(|p| uses_type(&p.ty, name))
+}
+
+/// Writes `export interface <name> { ... }` with one property per line.
+pub fn write_interface<W: Write>(
+    w: &mut W,
+    name: &[u8],
+    properties: &[Property],
+) -> std::io::Result<()> {
+    w.write_all(b"export interface ")?;
+    w.write_all(name)?;
+    w.write_all(b" {\n")?;
+    for property in properties {
+        w.write_all(b"  ")?;
+        write_property(w, property)?;
+        w.write_all(b";\n")?;
+    }
+    w.write_all(b"}\n")
+}
+
+fn write_property<W: Write>(w: &mut W, property: &Property) -> std::io::Result<()> {
+    write_key(w, &property.name)?;
+    w.write_all(if property.optional { b"?: " } else { b": " })?;
+    write_type(w, &property.ty)
+}
+
+/// Writes an object key, quoted unless it is a valid identifier.
+pub fn write_key<W: Write>(w: &mut W, name: &str) -> std::io::Result<()> {
+    let identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        w.write_all(name.as_bytes())
+    } else {
+        crate::json::write_str(w, name)
+    }
+}
+
+pub fn write_type<W: Write>(w: &mut W, ty: &Type) -> std::io::Result<()> {
+    match ty {
+        Type::String => w.write_all(b"string"),
+        Type::Number => w.write_all(b"number"),
+        Type::Boolean => w.write_all(b"boolean"),
+        Type::Undefined => w.write_all(b"undefined"),
+        Type::Unknown => w.write_all(b"unknown"),
+        Type::Literal(literal) => literal.write_js(w),
+        Type::Named(name) => w.write_all(name.as_bytes()),
+        Type::Record => w.write_all(b"Record<string, unknown>"),
+        Type::Array(items) => {
+            if let Type::Union(_) = **items {
+                w.write_all(b"(")?;
+                write_type(w, items)?;
+                w.write_all(b")[]")
+            } else {
+                write_type(w, items)?;
+                w.write_all(b"[]")
+            }
+        }
+        Type::Object(properties) if properties.is_empty() => w.write_all(b"{}"),
+        Type::Object(properties) => {
+            w.write_all(b"{ ")?;
+            for (idx, property) in properties.iter().enumerate() {
+                if idx > 0 {
+                    w.write_all(b"; ")?;
+                }
+                write_property(w, property)?;
+            }
+            w.write_all(b" }")
+        }
+        Type::Union(types) => {
+            for (idx, ty) in types.iter().enumerate() {
+                if idx > 0 {
+                    w.write_all(b" | ")?;
+                }
+                write_type(w, ty)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{properties, write_interface};
+    use crate::{config::Config, types::Content};
+
+    #[test]
+    fn infers_interfaces() {
+        let mut content = Content::new();
+        content.push_file(
+            "a.md",
+            "---\ntitle: A\ntags: [x, 1]\nauthor: { name: Ann, site: NULL }\n---\n",
+        );
+        content.push_file("b.mdx", "---\ntitle: B\ndate: 2022\nmy-key: true\n---\n");
+        content.push_file("c.md", "---\ntitle: \"open\n---\n");
+        content.push_file("d.md", "---\ntitle: D\nupdated: 2022-08-22\n---\n");
+        let mut out = Vec::new();
+        write_interface(
+            &mut out,
+            b"Posts",
+            &properties(&content, &[0, 1, 2, 3], None),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "export interface Posts {",
+                "  _id: string;",
+                "  _slug: string;",
+                "  _raw: string;",
+                "  _directory: string;",
+                "  _content: (string | JSXNode)[];",
+                "  _draft: boolean;",
+                "  _wordCount: number;",
+                "  _readingTime: number;",
+                "  _characterCount: number;",
+                "  _excerpt: { html: string; text: string };",
+                "  _headings: { level: number; text: string; id: string }[];",
+                "  _toc: TocEntry[];",
+                "  _prev: { id: string; slug: string; title: string; url: string } | undefined;",
+                "  _next: { id: string; slug: string; title: string; url: string } | undefined;",
+                "  title?: string;",
+                "  tags?: (string | number)[];",
+                "  author?: { name: string; site?: undefined };",
+                "  date?: number;",
+                "  \"my-key\"?: boolean;",
+                "  updated?: ISODate;",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+    #[test]
+    fn prefers_the_schema() {
+        let src = [
+            "[collections.posts.schema]",
+            "title = { type = \"string\", required = true }",
+            "status = { type = \"string\", enum = [\"draft\", \"live\"] }",
+            "rating = { type = \"integer\", default = 3 }",
+        ]
+        .join("\n");
+        let config = Config::from_toml(&src, Path::new("")).unwrap();
+        let schema = config.collection("posts").and_then(|c| c.schema.as_ref());
+        let mut content = Content::new();
+        content.push_file("a.md", "---\ntitle: 1\nstatus: draft\n---\n");
+        let mut out = Vec::new();
+        write_interface(&mut out, b"Posts", &properties(&content, &[0], schema)).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("  _content: string[];\n"));
+        assert!(
+            out.contains("  title: string;\n  status?: \"draft\" | \"live\";\n  rating: number;\n")
+        );
+    }
+}
@@ -1,6 +1,32 @@
+use std::path::PathBuf;
+
+use crate::{diagnostics::Diagnostic, threadpool::io_error};
+
 pub mod generate_route_params;
 pub mod process_markdown;
 pub mod process_mdx;
+pub mod validate_frontmatter;
 pub mod write_collections;
+pub mod write_json_schemas;
+pub mod write_pages;
+pub mod write_schemas;
 pub mod write_taxonomies;
-pub mod write_type_helpers;
+
+/// What a job wrote and what went wrong along the way.
+#[derive(Debug, Default)]
+pub struct JobOutput {
+    /// Files whose contents changed on disk.
+    pub written: Vec<PathBuf>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl JobOutput {
+    /// Records the result of writing `path` with `output::write_if_changed`.
+    pub(crate) fn record(&mut self, path: PathBuf, result: std::io::Result<bool>) {
+        match result {
+            Ok(true) => self.written.push(path),
+            Ok(false) => {}
+            Err(e) => self.diagnostics.push(io_error(&path, e)),
+        }
+    }
+}
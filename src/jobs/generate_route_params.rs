@@ -1,22 +1,32 @@
 use std::{
-    io::{BufWriter, Write},
-    path::Path,
+    io::Write,
+    path::{Path, PathBuf},
 };
 
-use crate::route_params::RouteParams;
+use crate::{
+    output::{is_generated, HEADER},
+    route_params::RouteParams,
+};
 
+/// Writes a `RouteParams` interface next to every route with params,
+/// returning the files whose contents changed.
 #[inline]
-pub fn generate<P: AsRef<Path>>(routes: P) -> std::io::Result<usize> {
-    let mut count = 0;
-    generate_route_params_rec(routes, &mut count)?;
-    Ok(count)
+pub fn generate<P: AsRef<Path>>(routes: P, filename: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    generate_route_params_rec(routes, filename, &mut files)?;
+    Ok(files)
 }
 
-fn generate_route_params_rec<P: AsRef<Path>>(root: P, count: &mut usize) -> std::io::Result<()> {
-    let dir = std::fs::read_dir(root)?;
+fn generate_route_params_rec<P: AsRef<Path>>(
+    root: P,
+    filename: &str,
+    files: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    let mut generated = false;
+    let dir = std::fs::read_dir(&root)?;
     for entry in dir.filter_map(|e| e.ok()) {
         if entry.path().is_dir() {
-            generate_route_params_rec(entry.path(), count)?;
+            generate_route_params_rec(entry.path(), filename, files)?;
         }
         if entry.path().is_file()
             && entry.path().file_stem().map(|s| s.to_string_lossy())
@@ -26,8 +36,7 @@ fn generate_route_params_rec<P: AsRef<Path>>(root: P, count: &mut usize) -> std:
             let mut route_params = RouteParams::from_path(&path);
             if let Some(next) = route_params.next() {
                 if let Some(dir) = entry.path().parent() {
-                    let file = std::fs::File::create(dir.join("generated.ts"))?;
-                    let mut writer = BufWriter::new(file);
+                    let mut writer: Vec<u8> = HEADER.to_vec();
                     let _ = writer.write(
                         b"export interface RouteParams extends Record<string, string> {\n",
                     )?;
@@ -36,11 +45,72 @@ fn generate_route_params_rec<P: AsRef<Path>>(root: P, count: &mut usize) -> std:
                         writer.write_fmt(format_args!("  \"{}\": string\n", param))?;
                     }
                     let _ = writer.write(b"}")?;
-                    writer.flush()?;
-                    *count += 1;
+                    let file = dir.join(filename);
+                    if crate::output::write_if_changed(&file, &writer)? {
+                        files.push(file);
+                    }
+                    generated = true;
                 }
             }
         }
     }
+    // The route lost its params since the last build
+    let file = root.as_ref().join(filename);
+    if !generated && is_generated(&file) {
+        crate::output::remove_if_exists(&file)?;
+    }
     Ok(())
 }
+
+/// Removes every `filename` below `routes` written by [`generate`],
+/// returning how many were deleted.
+pub fn clean<P: AsRef<Path>>(routes: P, filename: &str) -> std::io::Result<usize> {
+    let mut count = 0;
+    if routes.as_ref().is_dir() {
+        clean_rec(routes, filename, &mut count)?;
+    }
+    Ok(count)
+}
+
+fn clean_rec<P: AsRef<Path>>(root: P, filename: &str, count: &mut usize) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(root)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            clean_rec(&path, filename, count)?;
+        } else if path.file_name().map(|n| n == filename) == Some(true) && is_generated(&path) {
+            std::fs::remove_file(&path)?;
+            *count += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clean, generate};
+
+    #[test]
+    fn keeps_files_it_did_not_write() {
+        let routes = std::env::temp_dir().join(format!("qcc-routes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&routes);
+        std::fs::create_dir_all(routes.join("post/[id]")).unwrap();
+        std::fs::write(routes.join("post/[id]/index.tsx"), "").unwrap();
+        std::fs::write(routes.join("index.tsx"), "").unwrap();
+        let own = routes.join("generated.ts");
+        std::fs::write(&own, "export const mine = 1;").unwrap();
+        let written = generate(&routes, "generated.ts").unwrap();
+        assert_eq!(written, vec![routes.join("post/[id]/generated.ts")]);
+        assert!(own.is_file());
+        // The route loses its params.
+        std::fs::rename(routes.join("post/[id]"), routes.join("post/all")).unwrap();
+        generate(&routes, "generated.ts").unwrap();
+        assert!(!routes.join("post/all/generated.ts").exists());
+        assert!(own.is_file());
+        generate(&routes, "generated.ts").unwrap();
+        std::fs::rename(routes.join("post/all"), routes.join("post/[id]")).unwrap();
+        generate(&routes, "generated.ts").unwrap();
+        assert_eq!(clean(&routes, "generated.ts").unwrap(), 1);
+        assert!(own.is_file());
+        std::fs::remove_dir_all(&routes).unwrap();
+    }
+}
@@ -1,61 +1,78 @@
 use std::{
-    fs::File,
-    io::{BufWriter, Write},
+    io::Write,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use crate::{
+    cache::Cache,
+    config::Config,
     html_writer::Markdown,
-    types::{Config, Content, Page},
+    jobs::JobOutput,
+    navigation::Neighbours,
+    threadpool::io_error,
+    types::{Content, Page, PageMeta, Token},
 };
 
-pub fn process_all(content: Arc<Content>, config: Arc<Config>) -> std::io::Result<()> {
-    let mut html = String::new();
-    let outdir = config.output.join("files");
-    let input: String = config.input.to_string_lossy().to_string();
-    for token in content
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    for (token, neighbours) in content
         .tokens()
         .iter()
-        .filter(|t| content.path(t).ends_with(".md"))
+        .zip(cache.neighbours().iter().copied())
+        .filter(|(t, _)| content.path(t).ends_with(".md"))
+        .filter(|(t, _)| !cache.is_fresh(&content, t))
     {
-        let filename = content
-            .path(token)
-            .strip_prefix(&input)
-            .unwrap()
-            .trim_start_matches('/');
-        let outpath = crate::utils::output_path(&outdir, filename);
-        let file = File::create(outpath)?;
-        let mut w = BufWriter::new(file);
-        let path = content.path(token);
-        let content_vec = match Markdown::new(config.clone()).write_md(content.body_raw(token)) {
-            Ok(vec) => vec,
-            Err(e) => {
-                println!("{}", e);
-                continue;
-            }
-        };
-        // println!("{:?}", html_vec);
-        let input_str: String = config.input.display().to_string();
-        pulldown_cmark::html::push_html(
-            &mut html,
-            pulldown_cmark::Parser::new(content.body_raw(token)),
-        );
-        if let Some(stripped) = path
-            .strip_prefix(&input_str)
-            .and_then(|s| s.strip_prefix('/'))
-        {
-            w.write_all(b"export default ")?;
-            Page::write_json(
-                &stripped,
-                content.frontmatter_raw(token),
-                content.body_raw(token),
-                &content_vec,
-                &mut w,
-            )?;
-            w.write_all(b"\n")?;
-            w.flush()?;
-            html.clear();
+        // One broken file must not keep the others from being written.
+        match process_file(&content, token, &config, neighbours) {
+            Ok(written) => output.written.extend(written),
+            Err(e) => output
+                .diagnostics
+                .push(io_error(Path::new(content.path(token)), e)),
+        }
+    }
+    output
+}
+
+/// Renders one file, returning its module if the contents changed.
+pub fn process_file(
+    content: &Content,
+    token: &Token,
+    config: &Arc<Config>,
+    neighbours: Neighbours,
+) -> std::io::Result<Option<PathBuf>> {
+    let input: String = config.input.to_string_lossy().to_string();
+    let filename = content
+        .path(token)
+        .strip_prefix(&input)
+        .unwrap()
+        .trim_start_matches('/');
+    let outpath = crate::utils::output_path(config.files_dir(), filename);
+    let mut content_vec = Markdown::new(config.clone()).write_md(content.body_raw(token))?;
+    if let Ok(yaml) = content.frontmatter(token) {
+        if let Some(excerpt) = Markdown::frontmatter_excerpt(config.clone(), &yaml)? {
+            content_vec.excerpt = excerpt;
         }
     }
-    Ok(())
+    let mut w: Vec<u8> = Vec::with_capacity(content.source(token).len() * 2);
+    // The same brand as in `collections.ts`, so the types match.
+    if content
+        .frontmatter(token)
+        .is_ok_and(|yaml| yaml.has_dates())
+    {
+        w.write_all(crate::interfaces::ISO_DATE.as_bytes())?;
+    }
+    w.write_all(b"export default ")?;
+    Page::write_json(
+        filename,
+        content.frontmatter_raw(token),
+        content.body_raw(token),
+        &content_vec,
+        &crate::schema::defaults(config, content, token),
+        &PageMeta::new(config, content, token, neighbours),
+        &mut w,
+    )?;
+    w.write_all(b"\n")?;
+    let written = crate::output::write_if_changed(&outpath, &w)?;
+    Ok(written.then_some(outpath))
 }
@@ -1,76 +1,95 @@
 #![allow(dead_code)]
 use std::fmt::Write as _;
-use std::io::BufWriter;
-use std::{io::Write, sync::Arc};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use crate::cache::Cache;
 use crate::html_writer::Markdown;
-use crate::types::Page;
-use crate::{
-    imports::Imports,
-    types::{Config, Content},
-};
+use crate::jobs::JobOutput;
+use crate::navigation::Neighbours;
+use crate::threadpool::io_error;
+use crate::types::{Page, PageMeta, Token};
+use crate::{config::Config, imports::Imports, types::Content};
 
-pub fn process_all(content: Arc<Content>, config: Arc<Config>) -> std::io::Result<()> {
-    // Allocate a mutable string outside of the render loop.
-    // We need a buffer to write html into that we can dump when we
-    // hit a user imported component.
-    let mut buffer = String::new();
-    let outdir = config.output.join("files");
-    let input: String = config.input.to_string_lossy().to_string();
-    for token in content
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    for (token, neighbours) in content
         .tokens()
         .iter()
-        .filter(|t| content.path(t).ends_with(".mdx"))
+        .zip(cache.neighbours().iter().copied())
+        .filter(|(t, _)| content.path(t).ends_with(".mdx"))
+        .filter(|(t, _)| !cache.is_fresh(&content, t))
     {
-        let (imports, body_start) = crate::imports::Parser::new(content.body_raw(token))
-            .parse()
-            .unwrap_or((Imports::default(), 0));
-        let filename = content
-            .path(token)
-            .strip_prefix(&input)
-            .unwrap()
-            .trim_start_matches('/');
-        let outpath = crate::utils::output_path(&outdir, filename);
-        let file = std::fs::File::create(outpath)?;
-        let mut w = BufWriter::new(file);
-        let mut import_lines = content.body_raw(token)[..body_start].lines();
-        if let Some(next) = import_lines.next() {
-            w.write_all(next.trim().as_bytes())?;
-            for import in import_lines {
-                w.write_all(b";\n")?;
-                w.write_all(import.trim().as_bytes())?;
-            }
-            w.write_all(b";\n\n")?;
+        // One broken file must not keep the others from being written.
+        match process_file(&content, token, &config, neighbours) {
+            Ok(written) => output.written.extend(written),
+            Err(e) => output
+                .diagnostics
+                .push(io_error(Path::new(content.path(token)), e)),
         }
-        let content_vec = match Markdown::new(config.clone())
-            .write_mdx(&content.body_raw(token)[body_start..], &imports)
-        {
-            Ok(vec) => vec,
-            Err(e) => {
-                println!("{}", e);
-                continue;
-            }
-        };
-        let input_str: String = config.input.display().to_string();
-        let path = content.path(token);
-        if let Some(stripped) = path
-            .strip_prefix(&input_str)
-            .and_then(|s| s.strip_prefix('/'))
-        {
-            w.write_all(b"export default ")?;
-            Page::write_json(
-                &stripped,
-                content.frontmatter_raw(token),
-                content.body_raw(token),
-                &content_vec,
-                &mut w,
-            )?;
-            w.write_all(b"\n")?;
+    }
+    output
+}
+
+/// Renders one file, returning its module if the contents changed.
+pub fn process_file(
+    content: &Content,
+    token: &Token,
+    config: &Arc<Config>,
+    neighbours: Neighbours,
+) -> std::io::Result<Option<PathBuf>> {
+    let (imports, body_start) = crate::imports::Parser::new(content.body_raw(token))
+        .parse()
+        .unwrap_or((Imports::default(), 0));
+    let input: String = config.input.to_string_lossy().to_string();
+    let filename = content
+        .path(token)
+        .strip_prefix(&input)
+        .unwrap()
+        .trim_start_matches('/');
+    let outpath = crate::utils::output_path(config.files_dir(), filename);
+    let mut content_vec = Markdown::new(config.clone())
+        .write_mdx(&content.body_raw(token)[body_start..], &imports)?;
+    if let Ok(yaml) = content.frontmatter(token) {
+        if let Some(excerpt) = Markdown::frontmatter_excerpt(config.clone(), &yaml)? {
+            content_vec.excerpt = excerpt;
         }
-        // Drain the buffer after every iteration
-        buffer.clear();
     }
-    Ok(())
+    let mut w: Vec<u8> = Vec::with_capacity(content.source(token).len() * 2);
+    let mut import_lines = content.body_raw(token)[..body_start]
+        .lines()
+        .filter(|l| !l.trim().is_empty());
+    if let Some(next) = import_lines.next() {
+        w.write_all(next.trim().as_bytes())?;
+        for import in import_lines {
+            w.write_all(b";\n")?;
+            w.write_all(import.trim().as_bytes())?;
+        }
+        w.write_all(b";\n\n")?;
+    }
+    // The same brand as in `collections.ts`, so the types match.
+    if content
+        .frontmatter(token)
+        .is_ok_and(|yaml| yaml.has_dates())
+    {
+        w.write_all(crate::interfaces::ISO_DATE.as_bytes())?;
+    }
+    w.write_all(b"export default ")?;
+    Page::write_json(
+        filename,
+        content.frontmatter_raw(token),
+        content.body_raw(token),
+        &content_vec,
+        &crate::schema::defaults(config, content, token),
+        &PageMeta::new(config, content, token, neighbours),
+        &mut w,
+    )?;
+    w.write_all(b"\n")?;
+    let written = crate::output::write_if_changed(&outpath, &w)?;
+    Ok(written.then_some(outpath))
 }
 
 pub fn write_component<W: Write>(
@@ -148,8 +167,8 @@ pub fn write_component<W: Write>(
             pulldown_cmark::Event::Html(html) => {
                 let tag = html
                     .trim()
-                    .trim_start_matches(&['<', '/'])
-                    .trim_end_matches(&['>', '/']);
+                    .trim_start_matches(['<', '/'])
+                    .trim_end_matches(['>', '/']);
                 if imports.is_import(tag) && !buffer.is_empty() {
                     w.write_fmt(format_args!(
                         "    <div class=\"qc-content\" dangerouslySetInnerHTML={:?}/>\n",
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use crate::{cache::Cache, config::Config, jobs::JobOutput, types::Content};
+
+/// Checks every changed page against the schemas of its collections and
+/// taxonomies, and checks its publishing fields. Pages the cache considers
+/// fresh passed in the last build.
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    for token in content
+        .tokens()
+        .iter()
+        .filter(|t| !cache.is_fresh(&content, t))
+    {
+        output
+            .diagnostics
+            .extend(crate::schema::validate(&config, &content, token));
+        output
+            .diagnostics
+            .extend(crate::publish::validate(&config, &content, token));
+    }
+    output
+}
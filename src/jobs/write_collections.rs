@@ -1,21 +1,39 @@
-use std::{
-    collections::HashMap,
-    io::{BufWriter, Write},
-    path::Path,
-    sync::Arc,
-};
+use std::{collections::BTreeMap, io::Write, sync::Arc};
 
 use crate::{
-    types::{Config, Content},
+    cache::Cache,
+    config::Config,
+    diagnostics::Diagnostic,
+    interfaces::{properties, uses, write_interface, Property, ISO_DATE, TOC_ENTRY},
+    jobs::JobOutput,
+    sort::sort,
+    types::{Content, Token},
     utils::{write_camel_case, write_output_path, write_snake_case},
-    yaml::YamlError,
+    yaml::{is_valid_tag, YamlKind, YamlNode},
 };
 
-pub fn process_all(content: Arc<Content>, config: Arc<Config>) -> Result<(), YamlError> {
-    let mut collections: HashMap<String, Vec<usize>> = HashMap::with_capacity(content.len() * 3);
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    if cache.collections_fresh(&config) {
+        return output;
+    }
+    let collections = collect(&content, &config, &mut output.diagnostics);
+    let path = config.output.join(&config.naming.collections);
+    output.record(path, write(content.clone(), config, collections));
+    output
+}
+
+/// The members of every collection, by index into `Content::tokens`, in
+/// the configured order.
+pub fn collect(
+    content: &Content,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> BTreeMap<String, Vec<usize>> {
+    // Ordered so unchanged content produces byte-identical output.
+    let mut collections: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     for (idx, token) in content.tokens().iter().enumerate() {
-        let frontmatter = content.frontmatter(token)?;
-        for tag in frontmatter.get_tags() {
+        for tag in tags(content, token, diagnostics) {
             if let Some(xs) = collections.get_mut(tag) {
                 xs.push(idx);
             } else {
@@ -23,38 +41,110 @@ pub fn process_all(content: Arc<Content>, config: Arc<Config>) -> Result<(), Yam
             }
         }
     }
-    if let Err(e) = write(content.clone(), config, collections) {
-        println!("Collections error: {}", e);
+    for (tag, members) in collections.iter_mut() {
+        sort(content, members, config.sort_keys(config.collection(tag)));
+    }
+    collections
+}
+
+/// The valid tags of a file. A file with broken frontmatter has none, and
+/// tags that cannot become identifiers are skipped with a warning.
+pub fn tags<'a>(
+    content: &'a Content,
+    token: &'a Token,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<&'a str> {
+    let yaml = match content.frontmatter(token) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            diagnostics.push(Diagnostic::from_yaml(content, token, &e));
+            return Vec::new();
+        }
+    };
+    let raw = content.frontmatter_raw(token);
+    let src = raw.trim();
+    // Node ranges are relative to the trimmed source.
+    let trimmed = raw.len() - raw.trim_start().len();
+    let warning = |node: &YamlNode, message: String| {
+        let range = node.range();
+        Diagnostic::in_frontmatter(
+            content,
+            token,
+            message,
+            range.start + trimmed..range.end + trimmed,
+        )
+        .with_level(crate::diagnostics::Level::Warning)
+    };
+    let list = match yaml.tags_node() {
+        None => return Vec::new(),
+        Some(node) if node.kind != YamlKind::List => {
+            diagnostics.push(warning(
+                node,
+                format!("`tags` must be a list, found `{}`", node.slice(src)),
+            ));
+            return Vec::new();
+        }
+        Some(node) => node,
+    };
+    // Node ids are one based, so the list's children point past its index.
+    let id = yaml
+        .inner()
+        .iter()
+        .position(|n| std::ptr::eq(n, list))
+        .map(|idx| idx + 1);
+    let mut tags = Vec::new();
+    for node in yaml.inner().iter().filter(|n| Some(n.parent) == id) {
+        let tag = node.slice(src).trim_matches(['\'', '"']);
+        if node.kind == YamlKind::String && is_valid_tag(tag) {
+            tags.push(tag);
+        } else {
+            diagnostics.push(warning(
+                node,
+                format!(
+                    "invalid tag `{}` is ignored, tags must start with a letter and contain only letters, digits, spaces, `-` and `_`",
+                    tag
+                ),
+            ));
+        }
     }
-    Ok(())
+    tags
 }
 
 #[inline]
 pub fn write(
     content: Arc<Content>,
     config: Arc<Config>,
-    gen: HashMap<String, Vec<usize>>,
-) -> std::io::Result<()> {
+    gen: BTreeMap<String, Vec<usize>>,
+) -> std::io::Result<bool> {
     if content.tokens().is_empty() {
-        return Ok(());
+        return Ok(false);
+    }
+    let mut writer: Vec<u8> = Vec::with_capacity(content.len() * 64);
+    let interfaces: Vec<(&str, Vec<Property>)> = gen
+        .iter()
+        .map(|(tag, ids)| {
+            let schema = config.collection(tag).and_then(|c| c.schema.as_ref());
+            (export_name(&config, tag), properties(&content, ids, schema))
+        })
+        .collect();
+    let mut all: Vec<usize> = (0..content.len()).collect();
+    let all_properties = properties(&content, &all, None);
+    sort(&content, &mut all, &config.sort);
+    if uses(&all_properties, "JSXNode") {
+        writer.write_all(b"import type { JSXNode } from \"@builder.io/qwik\";\n")?;
     }
-    std::fs::create_dir_all(&config.output)?;
-    let file = std::fs::File::create(config.output.join("collections.ts"))?;
-    let mut writer = BufWriter::new(file);
-
-    let _ = writer.write(b"import type { Merge } from \"./generated-helpers\";\n")?;
     let input = config.input.to_string_lossy();
+    let files = format!("./{}/", config.naming.files);
     for (idx, token) in content.tokens().iter().enumerate() {
         let path = content.path(token).strip_prefix(&*input).unwrap();
-        let output_path = crate::utils::output_path(Path::new("./files/"), path);
-        let lossy = output_path.to_string_lossy();
         writer.write_fmt(format_args!("import q{} from \"", idx))?;
-        write_output_path(&*lossy, path, &mut writer)?;
+        write_output_path(&files, path, &mut writer)?;
         writer.write_all(b"\"\n")?;
     }
     let _ = writer.write(b"\n")?;
 
     for (tag, ids) in gen.iter() {
+        let tag = export_name(&config, tag);
         let mut id_iter = ids.iter();
         let _ = writer.write("export const ".as_bytes())?;
         write_snake_case(tag, &mut writer)?;
@@ -70,32 +160,35 @@ pub fn write(
         let _ = writer.write(b"];\n")?;
     }
     let _ = writer.write("export const all: All[] = [".as_bytes())?;
-    writer.write_all(" q1".as_bytes())?;
-    for id in 1..content.tokens().len() {
-        writer.write_fmt(format_args!(", q{}", id))?;
+    for (idx, id) in all.iter().enumerate() {
+        let separator = if idx > 0 { ", " } else { " " };
+        writer.write_fmt(format_args!("{}q{}", separator, id))?;
     }
     writer.write_all(b"];\n")?;
 
+    for (tag, properties) in interfaces.iter() {
+        let _ = writer.write(b"\n")?;
+        let mut name = Vec::with_capacity(tag.len());
+        write_camel_case(tag, &mut name)?;
+        write_interface(&mut writer, &name, properties)?;
+    }
     let _ = writer.write(b"\n")?;
-
-    for (tag, ids) in gen.iter() {
-        let mut id_iter = ids.iter();
-        let _ = writer.write("export type ".as_bytes())?;
-        write_camel_case(tag, &mut writer)?;
-        let _ = writer.write(" = Merge<".as_bytes())?;
-        if let Some(first) = id_iter.next() {
-            writer.write_fmt(format_args!("typeof q{}", first))?;
-            for id in id_iter {
-                writer.write_fmt(format_args!(" | typeof q{}", id))?;
-            }
-        }
-        let _ = writer.write(b">;\n")?;
+    write_interface(&mut writer, b"All", &all_properties)?;
+    if uses(&all_properties, "TocEntry") {
+        writer.write_all(b"\n")?;
+        writer.write_all(TOC_ENTRY.as_bytes())?;
     }
-    let _ = writer.write("export type All = Merge<".as_bytes())?;
-    writer.write_all("typeof q0".as_bytes())?;
-    for id in 1..content.tokens().len() - 1 {
-        writer.write_fmt(format_args!(" | typeof q{}", id))?;
+    if uses(&all_properties, "ISODate") {
+        writer.write_all(b"\n")?;
+        writer.write_all(ISO_DATE.as_bytes())?;
     }
-    let _ = writer.write(b">;\n")?;
-    Ok(())
+    crate::output::write_if_changed(&config.output.join(&config.naming.collections), &writer)
+}
+
+#[inline]
+pub fn export_name<'a>(config: &'a Config, tag: &'a str) -> &'a str {
+    config
+        .collection(tag)
+        .and_then(|c| c.name.as_deref())
+        .unwrap_or(tag)
 }
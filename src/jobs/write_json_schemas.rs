@@ -0,0 +1,241 @@
+use std::{collections::HashSet, io::Write, path::PathBuf, sync::Arc};
+
+use crate::{
+    cache::Cache,
+    config::{Config, GroupConfig},
+    interfaces::{frontmatter, Property, Type},
+    jobs::{write_collections, write_taxonomies, JobOutput},
+    json::write_str,
+    schema::{Field, FieldType, Literal},
+    types::Content,
+    utils::write_snake_case,
+};
+
+/// Writes a JSON Schema of the frontmatter of every collection and taxonomy,
+/// for editors to validate and complete it while authors type.
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    if cache.json_schemas_fresh(&config) {
+        return output;
+    }
+    // Problems with tags are reported by the collections job.
+    let collections = write_collections::collect(&content, &config, &mut Vec::new());
+    let taxonomies = write_taxonomies::collect(&content, &config);
+    let dir = config.output.join(&config.naming.json_schemas);
+    for (kind, groups) in [("collections", &collections), ("taxonomies", &taxonomies)] {
+        let dir = dir.join(kind);
+        let mut keep = HashSet::new();
+        for (key, members) in groups.iter() {
+            let group = match kind {
+                "collections" => config.collection(key),
+                _ => config.taxonomy(key),
+            };
+            let name = match kind {
+                "collections" => write_collections::export_name(&config, key),
+                _ => write_taxonomies::export_name(&config, key),
+            };
+            let path = file_name(&dir, name);
+            let schema = group.and_then(|g| g.schema.as_ref());
+            let properties = frontmatter(&content, members, schema);
+            let mut w = Vec::with_capacity(1024);
+            let result = write(&mut w, key, group, &properties)
+                .and_then(|()| crate::output::write_if_changed(&path, &w));
+            keep.insert(path.clone());
+            output.record(path, result);
+        }
+        // Groups that no longer have any members.
+        if let Err(e) = crate::output::remove_stale(&dir, &keep) {
+            output.record(dir, Err(e));
+        }
+    }
+    output
+}
+
+fn file_name(dir: &std::path::Path, name: &str) -> PathBuf {
+    let mut file = Vec::with_capacity(name.len() + 5);
+    let _ = write_snake_case(name, &mut file);
+    file.extend_from_slice(b".json");
+    dir.join(String::from_utf8_lossy(&file).as_ref())
+}
+
+/// Only fields the config declares as required are required, fields that
+/// every page happens to have may still be left out of a new one.
+pub fn write<W: Write>(
+    w: &mut W,
+    key: &str,
+    group: Option<&GroupConfig>,
+    properties: &[Property],
+) -> std::io::Result<()> {
+    let fields = group
+        .and_then(|g| g.schema.as_ref())
+        .map(|s| s.fields.as_slice())
+        .unwrap_or_default();
+    w.write_all(b"{\n  \"$schema\": \"http://json-schema.org/draft-07/schema#\",\n")?;
+    w.write_all(b"  \"title\": ")?;
+    write_str(w, group.and_then(|g| g.name.as_deref()).unwrap_or(key))?;
+    w.write_all(b",\n  \"type\": \"object\",\n  \"properties\": {")?;
+    for (idx, property) in properties.iter().enumerate() {
+        w.write_all(if idx > 0 { b",\n    " } else { b"\n    " })?;
+        write_str(w, &property.name)?;
+        w.write_all(b": ")?;
+        let field = fields.iter().find(|f| f.name == property.name);
+        write_property(w, property, field)?;
+    }
+    w.write_all(b"\n  },\n  \"required\": [")?;
+    let required = fields.iter().filter(|f| f.required);
+    for (idx, field) in required.enumerate() {
+        if idx > 0 {
+            w.write_all(b", ")?;
+        }
+        write_str(w, &field.name)?;
+    }
+    w.write_all(b"]\n}\n")
+}
+
+fn write_property<W: Write>(
+    w: &mut W,
+    property: &Property,
+    field: Option<&Field>,
+) -> std::io::Result<()> {
+    let Some(field) = field else {
+        return write_type(w, &property.ty);
+    };
+    // `Type` has no integers, and defaults are only known to the config.
+    let integer = field.kind == FieldType::Integer && field.allowed.is_empty();
+    match (integer, &field.default) {
+        (false, None) => write_type(w, &property.ty),
+        (integer, default) => {
+            w.write_all(b"{ ")?;
+            if integer {
+                w.write_all(b"\"type\": \"integer\"")?;
+            } else {
+                write_type_keys(w, &property.ty)?;
+            }
+            if let Some(default) = default {
+                w.write_all(b", \"default\": ")?;
+                default.write_js(w)?;
+            }
+            w.write_all(b" }")
+        }
+    }
+}
+
+pub fn write_type<W: Write>(w: &mut W, ty: &Type) -> std::io::Result<()> {
+    if matches!(ty, Type::Unknown) || matches!(ty, Type::Named(n) if *n != "ISODate") {
+        return w.write_all(b"{}");
+    }
+    w.write_all(b"{ ")?;
+    write_type_keys(w, ty)?;
+    w.write_all(b" }")
+}
+
+/// The keywords describing `ty`, without the surrounding braces.
+fn write_type_keys<W: Write>(w: &mut W, ty: &Type) -> std::io::Result<()> {
+    match ty {
+        Type::String => w.write_all(b"\"type\": \"string\""),
+        Type::Number => w.write_all(b"\"type\": \"number\""),
+        Type::Boolean => w.write_all(b"\"type\": \"boolean\""),
+        Type::Undefined => w.write_all(b"\"type\": \"null\""),
+        // Authors write dates, frontmatter parsing normalizes them.
+        Type::Named("ISODate") => w.write_all(
+            b"\"type\": \"string\", \"anyOf\": [{ \"format\": \"date\" }, { \"format\": \"date-time\" }]",
+        ),
+        // Anything goes, which takes no keywords at all.
+        Type::Unknown | Type::Named(_) => Ok(()),
+        Type::Literal(literal) => {
+            w.write_all(b"\"const\": ")?;
+            literal.write_js(w)
+        }
+        Type::Record => w.write_all(b"\"type\": \"object\""),
+        Type::Array(items) => {
+            w.write_all(b"\"type\": \"array\", \"items\": ")?;
+            write_type(w, items)
+        }
+        Type::Object(properties) => {
+            w.write_all(b"\"type\": \"object\", \"properties\": {")?;
+            for (idx, property) in properties.iter().enumerate() {
+                w.write_all(if idx > 0 { b", " } else { b" " })?;
+                write_str(w, &property.name)?;
+                w.write_all(b": ")?;
+                write_type(w, &property.ty)?;
+            }
+            w.write_all(b" }")
+        }
+        Type::Union(types) => {
+            let literals: Vec<&Literal> = types
+                .iter()
+                .filter_map(|ty| match ty {
+                    Type::Literal(literal) => Some(literal),
+                    _ => None,
+                })
+                .collect();
+            if literals.len() == types.len() {
+                w.write_all(b"\"enum\": [")?;
+                for (idx, literal) in literals.iter().enumerate() {
+                    if idx > 0 {
+                        w.write_all(b", ")?;
+                    }
+                    literal.write_js(w)?;
+                }
+                return w.write_all(b"]");
+            }
+            w.write_all(b"\"anyOf\": [")?;
+            for (idx, ty) in types.iter().enumerate() {
+                if idx > 0 {
+                    w.write_all(b", ")?;
+                }
+                write_type(w, ty)?;
+            }
+            w.write_all(b"]")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::write;
+    use crate::{config::Config, interfaces::frontmatter, types::Content};
+
+    #[test]
+    fn writes_json_schema() {
+        let src = [
+            "[collections.reviews]",
+            "name = \"Reviews\"",
+            "[collections.reviews.schema]",
+            "rating = { type = \"integer\", required = true }",
+            "status = { type = \"string\", enum = [\"draft\", \"live\"], default = \"live\" }",
+        ]
+        .join("\n");
+        let config = Config::from_toml(&src, Path::new("")).unwrap();
+        let mut content = Content::new();
+        content.push_file(
+            "src/content/a.md",
+            "---\ntitle: A\ntags: [reviews]\nrating: 4\n---\n",
+        );
+        let group = config.collection("reviews");
+        let properties = frontmatter(&content, &[0], group.and_then(|g| g.schema.as_ref()));
+        let mut out = Vec::new();
+        write(&mut out, "reviews", group, &properties).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "{",
+                "  \"$schema\": \"http://json-schema.org/draft-07/schema#\",",
+                "  \"title\": \"Reviews\",",
+                "  \"type\": \"object\",",
+                "  \"properties\": {",
+                "    \"title\": { \"type\": \"string\" },",
+                "    \"tags\": { \"type\": \"array\", \"items\": { \"type\": \"string\" } },",
+                "    \"rating\": { \"type\": \"integer\" },",
+                "    \"status\": { \"enum\": [\"draft\", \"live\"], \"default\": \"live\" }",
+                "  },",
+                "  \"required\": [\"rating\"]",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::HashSet,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    cache::Cache,
+    config::{Config, GroupRef, Pagination},
+    diagnostics::{Diagnostic, Level},
+    jobs::{write_collections, write_taxonomies, JobOutput},
+    output::{is_generated, HEADER},
+    types::Content,
+    utils::write_output_path,
+};
+
+/// Splits the paginated collections and taxonomies into one module per
+/// page, so listing routes only import the content they show. Modules start
+/// with [`HEADER`], and only modules with it are ever removed.
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    let dir = config.output.join(&config.naming.pages);
+    let mut listings = HashSet::new();
+    if !config.pagination.is_empty() {
+        if cache.pages_fresh(&config) {
+            return output;
+        }
+        write_listings(&content, &config, &dir, &mut listings, &mut output);
+    }
+    // Listings no longer in the config, all of them once pagination is off.
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.is_dir() && !listings.contains(&path) {
+                if let Err(e) = remove_stale_pages(&path, &HashSet::new()) {
+                    output.record(path, Err(e));
+                }
+            }
+        }
+    }
+    if config.pagination.is_empty() {
+        if let Err(e) = remove_dir_if_empty(&dir) {
+            output.record(dir, Err(e));
+        }
+    }
+    output
+}
+
+/// Writes the modules of every configured listing, adding their directories
+/// to `listings`.
+fn write_listings(
+    content: &Content,
+    config: &Config,
+    dir: &Path,
+    listings: &mut HashSet<PathBuf>,
+    output: &mut JobOutput,
+) {
+    // Problems with tags are reported by the collections job.
+    let collections = write_collections::collect(content, config, &mut Vec::new());
+    let taxonomies = write_taxonomies::collect(content, config);
+    for listing in config.pagination.iter() {
+        let dir = dir.join(&listing.name);
+        let members = match &listing.group {
+            GroupRef::Collection(tag) => collections.get(tag),
+            GroupRef::Taxonomy(key) => taxonomies.get(key),
+        };
+        if members.is_none() {
+            let (kind, key) = match &listing.group {
+                GroupRef::Collection(tag) => ("collection", tag),
+                GroupRef::Taxonomy(key) => ("taxonomy", key),
+            };
+            let message = format!(
+                "pagination `{}` is empty, no page is in the {} `{}`",
+                listing.name, kind, key
+            );
+            output.diagnostics.push(
+                Diagnostic::new(dir.display().to_string(), message).with_level(Level::Warning),
+            );
+        }
+        let pages = pages(members.map(Vec::as_slice).unwrap_or_default(), listing);
+        let mut keep = HashSet::new();
+        for (idx, page) in pages.iter().enumerate() {
+            let path = dir.join(format!("{}.ts", idx + 1));
+            let mut w = HEADER.to_vec();
+            let result = write_page(&mut w, content, config, page, idx + 1, pages.len())
+                .and_then(|()| crate::output::write_if_changed(&path, &w));
+            keep.insert(path.clone());
+            output.record(path, result);
+        }
+        let path = dir.join("index.ts");
+        let mut w = HEADER.to_vec();
+        let result = write_index(&mut w, pages.len())
+            .and_then(|()| crate::output::write_if_changed(&path, &w));
+        keep.insert(path.clone());
+        output.record(path, result);
+        // Pages past the new last page.
+        if let Err(e) = remove_stale_pages(&dir, &keep) {
+            output.record(dir.clone(), Err(e));
+        }
+        listings.insert(dir);
+    }
+}
+
+/// Removes the modules this tool wrote in `dir` that are not in `keep`, and
+/// `dir` itself once nothing is left in it. A missing `dir` is not an error.
+fn remove_stale_pages(dir: &Path, keep: &HashSet<PathBuf>) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_file() && !keep.contains(&path) && is_generated(&path) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    remove_dir_if_empty(dir)
+}
+
+fn remove_dir_if_empty(dir: &Path) -> std::io::Result<()> {
+    match std::fs::read_dir(dir).map(|mut entries| entries.next().is_none()) {
+        Ok(true) => std::fs::remove_dir(dir),
+        Ok(false) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The members of every page. An empty listing still has a first page,
+/// so its route renders.
+fn pages<'a>(members: &'a [usize], listing: &Pagination) -> Vec<&'a [usize]> {
+    if members.is_empty() {
+        return vec![members];
+    }
+    members.chunks(listing.per_page).collect()
+}
+
+/// Writes the module of page `number`, counting from one.
+pub fn write_page<W: Write>(
+    w: &mut W,
+    content: &Content,
+    config: &Config,
+    page: &[usize],
+    number: usize,
+    total: usize,
+) -> std::io::Result<()> {
+    // Page modules sit one directory below `naming.pages`.
+    let depth = Path::new(&config.naming.pages).components().count() + 1;
+    let files = format!("{}{}/", "../".repeat(depth), config.naming.files);
+    let input = config.input.to_string_lossy();
+    for id in page {
+        let path = content.path(&content.tokens()[*id]);
+        let path = path.strip_prefix(&*input).unwrap_or(path);
+        w.write_fmt(format_args!("import q{} from \"", id))?;
+        write_output_path(&files, path, w)?;
+        w.write_all(b"\"\n")?;
+    }
+    if !page.is_empty() {
+        w.write_all(b"\n")?;
+    }
+    w.write_all(b"export const items = [")?;
+    for (idx, id) in page.iter().enumerate() {
+        let separator = if idx > 0 { ", " } else { " " };
+        w.write_fmt(format_args!("{}q{}", separator, id))?;
+    }
+    w.write_all(b"];\n")?;
+    w.write_fmt(format_args!("export const pageNumber = {};\n", number))?;
+    w.write_fmt(format_args!("export const totalPages = {};\n", total))?;
+    let prev = (number > 1).then(|| number - 1);
+    let next = (number < total).then(|| number + 1);
+    for (name, value) in [("prev", prev), ("next", next)] {
+        let value = value.map_or_else(|| "undefined".to_owned(), |n| n.to_string());
+        w.write_fmt(format_args!(
+            "export const {}: number | undefined = {};\n",
+            name, value
+        ))?;
+    }
+    Ok(())
+}
+
+/// Writes the valid `page_number` route params, and a loader for each page.
+pub fn write_index<W: Write>(w: &mut W, total: usize) -> std::io::Result<()> {
+    w.write_fmt(format_args!("export const totalPages = {};\n", total))?;
+    w.write_all(b"export const pageNumbers = [")?;
+    for number in 1..=total {
+        let separator = if number > 1 { ", " } else { " " };
+        w.write_fmt(format_args!("{}\"{}\"", separator, number))?;
+    }
+    w.write_all(b"];\nexport const pages = {\n")?;
+    for number in 1..=total {
+        w.write_fmt(format_args!(
+            "  \"{}\": () => import(\"./{}\"),\n",
+            number, number
+        ))?;
+    }
+    w.write_all(b"};\n")
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, sync::Arc};
+
+    use super::{process_all, write_index, write_page};
+    use crate::{cache::Cache, config::Config, output::HEADER, types::Content};
+
+    #[test]
+    fn writes_page_modules() {
+        let config = Config::new("content".into(), PathBuf::new(), PathBuf::new());
+        let mut content = Content::new();
+        content.push_file("content/posts/a.md", "");
+        content.push_file("content/posts/b.mdx", "");
+        let mut out = Vec::new();
+        write_page(&mut out, &content, &config, &[1, 0], 2, 3).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "import q1 from \"../../files/posts_b.mdx\"",
+                "import q0 from \"../../files/posts_a.md\"",
+                "",
+                "export const items = [ q1, q0];",
+                "export const pageNumber = 2;",
+                "export const totalPages = 3;",
+                "export const prev: number | undefined = 1;",
+                "export const next: number | undefined = 3;",
+                "",
+            ]
+            .join("\n")
+        );
+        let mut out = Vec::new();
+        write_index(&mut out, 2).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "export const totalPages = 2;",
+                "export const pageNumbers = [ \"1\", \"2\"];",
+                "export const pages = {",
+                "  \"1\": () => import(\"./1\"),",
+                "  \"2\": () => import(\"./2\"),",
+                "};",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+    #[test]
+    fn removes_only_its_own_pages() {
+        let output = std::env::temp_dir().join(format!("qcc-pages-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&output);
+        let pages = output.join("pages");
+        std::fs::create_dir_all(pages.join("posts")).unwrap();
+        std::fs::create_dir_all(pages.join("mine")).unwrap();
+        std::fs::write(pages.join("posts/1.ts"), HEADER).unwrap();
+        std::fs::write(pages.join("mine/1.ts"), HEADER).unwrap();
+        std::fs::write(pages.join("mine/notes.ts"), "export {}").unwrap();
+        let config = Config::new("content".into(), output.clone(), PathBuf::new());
+        let content = Arc::new(Content::new());
+        let cache = Arc::new(Cache::new(&config, &content, false));
+        let result = process_all(content, Arc::new(config), cache);
+        assert!(result.diagnostics.is_empty());
+        assert!(!pages.join("posts").exists());
+        assert!(!pages.join("mine/1.ts").exists());
+        assert!(pages.join("mine/notes.ts").is_file());
+        std::fs::remove_dir_all(&output).unwrap();
+    }
+}
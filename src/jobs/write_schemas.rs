@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, io::Write, sync::Arc};
+
+use crate::{
+    cache::Cache,
+    config::Config,
+    interfaces::{properties, uses, write_key, Property, Type, ISO_DATE, TOC_ENTRY},
+    jobs::{write_collections, write_taxonomies, JobOutput},
+    types::Content,
+    utils::write_snake_case,
+};
+
+/// Writes Zod schemas matching the interfaces of `collections.ts` and
+/// `taxonomies.ts`, for validating content at runtime.
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    let mut output = JobOutput::default();
+    if cache.schemas_fresh(&config) {
+        return output;
+    }
+    // Problems with tags are reported by the collections job.
+    let collections = write_collections::collect(&content, &config, &mut Vec::new());
+    let taxonomies = write_taxonomies::collect(&content, &config);
+    let path = config.output.join(&config.naming.schemas);
+    output.record(path, write(&content, &config, &collections, &taxonomies));
+    output
+}
+
+pub fn write(
+    content: &Content,
+    config: &Config,
+    collections: &BTreeMap<String, Vec<usize>>,
+    taxonomies: &BTreeMap<String, Vec<usize>>,
+) -> std::io::Result<bool> {
+    if content.is_empty() {
+        return Ok(false);
+    }
+    let collections: Vec<(&str, Vec<Property>)> = collections
+        .iter()
+        .map(|(tag, ids)| {
+            let schema = config.collection(tag).and_then(|c| c.schema.as_ref());
+            (
+                write_collections::export_name(config, tag),
+                properties(content, ids, schema),
+            )
+        })
+        .collect();
+    let taxonomies: Vec<(&str, Vec<Property>)> = taxonomies
+        .iter()
+        .map(|(key, ids)| {
+            let schema = config.taxonomy(key).and_then(|c| c.schema.as_ref());
+            (
+                write_taxonomies::export_name(config, key),
+                properties(content, ids, schema),
+            )
+        })
+        .collect();
+    let all: Vec<usize> = (0..content.len()).collect();
+    let all = properties(content, &all, None);
+
+    let mut writer: Vec<u8> = Vec::with_capacity(content.len() * 256);
+    writer.write_all(b"import { z } from \"zod\";\n")?;
+    if uses(&all, "JSXNode") {
+        writer.write_all(b"import type { JSXNode } from \"@builder.io/qwik\";\n")?;
+    }
+    if uses(&all, "TocEntry") {
+        writer.write_all(b"\n")?;
+        writer.write_all(TOC_ENTRY.as_bytes())?;
+        writer.write_all(TOC_ENTRY_SCHEMA.as_bytes())?;
+    }
+    if uses(&all, "ISODate") {
+        writer.write_all(b"\n")?;
+        writer.write_all(ISO_DATE.as_bytes())?;
+        writer.write_all(ISO_DATE_SCHEMA.as_bytes())?;
+    }
+    for (name, groups) in [("collections", &collections), ("taxonomies", &taxonomies)] {
+        writer.write_fmt(format_args!("\nexport const {} = {{\n", name))?;
+        for (key, properties) in groups.iter() {
+            writer.write_all(b"  ")?;
+            write_snake_case(key, &mut writer)?;
+            writer.write_all(b": ")?;
+            write_object(&mut writer, properties, 2)?;
+            writer.write_all(b",\n")?;
+        }
+        writer.write_all(b"};\n")?;
+    }
+    writer.write_all(b"\nexport const all = ")?;
+    write_object(&mut writer, &all, 0)?;
+    writer.write_all(b";\n")?;
+    crate::output::write_if_changed(&config.output.join(&config.naming.schemas), &writer)
+}
+
+/// Recursive, so it has to be declared with its type.
+const TOC_ENTRY_SCHEMA: &str = "const tocEntry: z.ZodType<TocEntry> = z.lazy(() =>
+  z.object({
+    level: z.number(),
+    text: z.string(),
+    id: z.string(),
+    children: z.array(tocEntry),
+  })
+);
+";
+
+const ISO_DATE_SCHEMA: &str = "const isoDate = z.custom<ISODate>(
+  (value) => typeof value === \"string\" && !Number.isNaN(Date.parse(value))
+);
+";
+
+/// Writes `z.object({ ... })` with one property per line.
+fn write_object<W: Write>(
+    w: &mut W,
+    properties: &[Property],
+    indent: usize,
+) -> std::io::Result<()> {
+    w.write_all(b"z.object({\n")?;
+    for property in properties {
+        w.write_fmt(format_args!("{:indent$}", "", indent = indent + 2))?;
+        write_property(w, property)?;
+        w.write_all(b",\n")?;
+    }
+    w.write_fmt(format_args!("{:indent$}}})", "", indent = indent))
+}
+
+fn write_property<W: Write>(w: &mut W, property: &Property) -> std::io::Result<()> {
+    write_key(w, &property.name)?;
+    w.write_all(b": ")?;
+    write_zod(w, &property.ty)?;
+    if property.optional {
+        w.write_all(b".optional()")?;
+    }
+    Ok(())
+}
+
+pub fn write_zod<W: Write>(w: &mut W, ty: &Type) -> std::io::Result<()> {
+    match ty {
+        Type::String => w.write_all(b"z.string()"),
+        Type::Number => w.write_all(b"z.number()"),
+        Type::Boolean => w.write_all(b"z.boolean()"),
+        Type::Undefined => w.write_all(b"z.undefined()"),
+        Type::Unknown => w.write_all(b"z.unknown()"),
+        Type::Literal(literal) => {
+            w.write_all(b"z.literal(")?;
+            literal.write_js(w)?;
+            w.write_all(b")")
+        }
+        Type::Named("TocEntry") => w.write_all(b"tocEntry"),
+        Type::Named("ISODate") => w.write_all(b"isoDate"),
+        Type::Named(name) => w.write_fmt(format_args!("z.custom<{}>()", name)),
+        Type::Record => w.write_all(b"z.record(z.unknown())"),
+        Type::Array(items) => {
+            w.write_all(b"z.array(")?;
+            write_zod(w, items)?;
+            w.write_all(b")")
+        }
+        Type::Object(properties) => {
+            w.write_all(b"z.object({ ")?;
+            for (idx, property) in properties.iter().enumerate() {
+                if idx > 0 {
+                    w.write_all(b", ")?;
+                }
+                write_property(w, property)?;
+            }
+            w.write_all(b" })")
+        }
+        Type::Union(types) => {
+            w.write_all(b"z.union([")?;
+            for (idx, ty) in types.iter().enumerate() {
+                if idx > 0 {
+                    w.write_all(b", ")?;
+                }
+                write_zod(w, ty)?;
+            }
+            w.write_all(b"])")
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::write_zod;
+    use crate::{
+        interfaces::{Property, Type},
+        schema::Literal,
+    };
+
+    #[test]
+    fn writes_zod_types() {
+        let ty = Type::Object(vec![
+            Property {
+                name: "tags".to_owned(),
+                ty: Type::Array(Box::new(Type::Union(vec![Type::String, Type::Number]))),
+                optional: false,
+            },
+            Property {
+                name: "my-status".to_owned(),
+                ty: Type::Literal(Literal::String("draft".to_owned())),
+                optional: true,
+            },
+        ]);
+        let mut out = Vec::new();
+        write_zod(&mut out, &ty).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "z.object({ tags: z.array(z.union([z.string(), z.number()])), \"my-status\": z.literal(\"draft\").optional() })"
+        );
+    }
+}
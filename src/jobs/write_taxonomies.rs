@@ -1,22 +1,32 @@
-use std::{
-    collections::HashMap,
-    io::{BufWriter, Write},
-    path::Path,
-    sync::Arc,
-};
+use std::{collections::BTreeMap, io::Write, path::Path, sync::Arc};
 
 use crate::{
-    types::{Config, Content},
+    cache::Cache,
+    config::Config,
+    interfaces::{properties, uses, write_interface, Property, ISO_DATE, TOC_ENTRY},
+    jobs::JobOutput,
+    sort::sort,
+    types::Content,
     utils::{write_camel_case, write_output_path, write_snake_case},
 };
 
-pub fn process_all(content: Arc<Content>, config: Arc<Config>) {
-    let mut taxonomies: HashMap<String, Vec<usize>> = HashMap::default();
+pub fn process_all(content: Arc<Content>, config: Arc<Config>, cache: Arc<Cache>) -> JobOutput {
+    if cache.taxonomies_fresh(&config) {
+        return JobOutput::default();
+    }
+    let taxonomies = collect(&content, &config);
+    let mut output = JobOutput::default();
+    let path = config.output.join(&config.naming.taxonomies);
+    output.record(path, write(content.clone(), config.clone(), &taxonomies));
+    output
+}
+
+/// The members of every taxonomy, by index into `Content::tokens`, in the
+/// configured order.
+pub fn collect(content: &Content, config: &Config) -> BTreeMap<String, Vec<usize>> {
+    let mut taxonomies: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     for (id, token) in content.tokens().iter().enumerate() {
-        let path: &Path = content.path(token).as_ref();
-        let dir = path.strip_prefix(&config.input).unwrap().parent().unwrap();
-        for segment in dir {
-            let key = segment.to_str().unwrap();
+        for key in taxonomies_of(config, content.path(token)) {
             if let Some(vec) = taxonomies.get_mut(key) {
                 vec.push(id)
             } else {
@@ -24,39 +34,54 @@ pub fn process_all(content: Arc<Content>, config: Arc<Config>) {
             }
         }
     }
-    if let Err(e) = write(content.clone(), config.clone(), &taxonomies) {
-        println!("Taxonomies Error: {}", e);
+    for (key, members) in taxonomies.iter_mut() {
+        sort(content, members, config.sort_keys(config.taxonomy(key)));
     }
+    taxonomies
+}
+
+/// The directories between the input root and the file, outermost first.
+pub fn taxonomies_of<'a>(config: &Config, path: &'a str) -> Vec<&'a str> {
+    Path::new(path)
+        .strip_prefix(&config.input)
+        .ok()
+        .and_then(|p| p.parent())
+        .map(|dir| dir.iter().filter_map(|s| s.to_str()).collect())
+        .unwrap_or_default()
 }
 
 #[inline]
 pub fn write(
     content: Arc<Content>,
     config: Arc<Config>,
-    gen: &HashMap<String, Vec<usize>>,
-) -> std::io::Result<()> {
+    gen: &BTreeMap<String, Vec<usize>>,
+) -> std::io::Result<bool> {
     if gen.is_empty() {
-        return Ok(());
+        return Ok(false);
+    }
+    let mut writer: Vec<u8> = Vec::with_capacity(content.len() * 64);
+    let interfaces: Vec<(&str, Vec<Property>)> = gen
+        .iter()
+        .map(|(key, ids)| {
+            let schema = config.taxonomy(key).and_then(|c| c.schema.as_ref());
+            (export_name(&config, key), properties(&content, ids, schema))
+        })
+        .collect();
+    if interfaces.iter().any(|(_, p)| uses(p, "JSXNode")) {
+        writer.write_all(b"import type { JSXNode } from \"@builder.io/qwik\";\n")?;
     }
-    std::fs::create_dir_all(&config.output)?;
-    let file = std::fs::File::create(&config.output.join("taxonomies.ts"))?;
-    let mut writer = BufWriter::new(file);
-
     let input = config.input.to_string_lossy();
+    let files = format!("./{}/", config.naming.files);
     for (idx, token) in content.tokens().iter().enumerate() {
         let path = content.path(token).strip_prefix(&*input).unwrap();
-        let output_path = crate::utils::output_path(Path::new("./files/"), path);
-        let lossy = output_path.to_string_lossy();
         writer.write_fmt(format_args!("import q{} from \"", idx))?;
-        write_output_path(&*lossy, path, &mut writer)?;
+        write_output_path(&files, path, &mut writer)?;
         writer.write_all(b"\"\n")?;
     }
-
-    let _ = writer.write(b"\n")?;
-    let _ = writer.write(b"import type { Merge } from \"./generated-helpers\";\n")?;
     let _ = writer.write(b"\n")?;
 
     for (tag, ids) in gen.iter() {
+        let tag = export_name(&config, tag);
         let mut id_iter = ids.iter();
         let _ = writer.write("export const ".as_bytes())?;
         write_snake_case(tag, &mut writer)?;
@@ -72,20 +97,27 @@ pub fn write(
         let _ = writer.write(b"];\n")?;
     }
 
-    let _ = writer.write(b"\n")?;
-
-    for (tag, ids) in gen.iter() {
-        let mut id_iter = ids.iter();
-        let _ = writer.write("export type ".as_bytes())?;
-        write_camel_case(tag, &mut writer)?;
-        let _ = writer.write(" = Merge<".as_bytes())?;
-        if let Some(first) = id_iter.next() {
-            writer.write_fmt(format_args!("typeof q{}", first))?;
-            for id in id_iter {
-                writer.write_fmt(format_args!(" | typeof q{}", id))?;
-            }
-        }
-        let _ = writer.write(b">;\n")?;
+    for (tag, properties) in interfaces.iter() {
+        let _ = writer.write(b"\n")?;
+        let mut name = Vec::with_capacity(tag.len());
+        write_camel_case(tag, &mut name)?;
+        write_interface(&mut writer, &name, properties)?;
+    }
+    if interfaces.iter().any(|(_, p)| uses(p, "TocEntry")) {
+        writer.write_all(b"\n")?;
+        writer.write_all(TOC_ENTRY.as_bytes())?;
+    }
+    if interfaces.iter().any(|(_, p)| uses(p, "ISODate")) {
+        writer.write_all(b"\n")?;
+        writer.write_all(ISO_DATE.as_bytes())?;
     }
-    Ok(())
+    crate::output::write_if_changed(&config.output.join(&config.naming.taxonomies), &writer)
+}
+
+#[inline]
+pub fn export_name<'a>(config: &'a Config, taxonomy: &'a str) -> &'a str {
+    config
+        .taxonomy(taxonomy)
+        .and_then(|c| c.name.as_deref())
+        .unwrap_or(taxonomy)
 }
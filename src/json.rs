@@ -0,0 +1,290 @@
+use std::io::Write;
+
+/// Writes `value` as a quoted JSON string.
+pub fn write_str<W: Write>(w: &mut W, value: &str) -> std::io::Result<()> {
+    w.write_all(b"\"")?;
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            c if (c as u32) < 0x20 => "",
+            _ => continue,
+        };
+        w.write_all(&value.as_bytes()[start..idx])?;
+        if escaped.is_empty() {
+            w.write_fmt(format_args!("\\u{:04x}", c as u32))?;
+        } else {
+            w.write_all(escaped.as_bytes())?;
+        }
+        start = idx + c.len_utf8();
+    }
+    w.write_all(&value.as_bytes()[start..])?;
+    w.write_all(b"\"")
+}
+
+/// Writes a JSON array of strings.
+pub fn write_str_array<W: Write, S: AsRef<str>>(w: &mut W, values: &[S]) -> std::io::Result<()> {
+    w.write_all(b"[")?;
+    for (idx, value) in values.iter().enumerate() {
+        if idx > 0 {
+            w.write_all(b", ")?;
+        }
+        write_str(w, value.as_ref())?;
+    }
+    w.write_all(b"]")
+}
+
+/// A parsed JSON value. Only used for the small requests of `serve`, so
+/// objects keep their keys in order and numbers are plain floats.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(src: &str) -> Result<Json, String> {
+        let mut parser = Parser { src, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(value),
+            Some(c) => Err(parser.unexpected(c)),
+        }
+    }
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        match self {
+            Json::Null => w.write_all(b"null"),
+            Json::Bool(b) => w.write_fmt(format_args!("{}", b)),
+            Json::Number(n) => w.write_fmt(format_args!("{}", n)),
+            Json::String(s) => write_str(w, s),
+            Json::Array(values) => {
+                w.write_all(b"[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        w.write_all(b", ")?;
+                    }
+                    value.write(w)?;
+                }
+                w.write_all(b"]")
+            }
+            Json::Object(entries) => {
+                w.write_all(b"{")?;
+                for (idx, (key, value)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        w.write_all(b", ")?;
+                    }
+                    write_str(w, key)?;
+                    w.write_all(b": ")?;
+                    value.write(w)?;
+                }
+                w.write_all(b"}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+    fn unexpected(&self, c: char) -> String {
+        format!("unexpected `{}` at column {}", c, self.pos + 1)
+    }
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!(
+                "expected `{}`, found `{}` at column {}",
+                expected, c, self.pos
+            )),
+            None => Err("unexpected end of input".to_owned()),
+        }
+    }
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err("unexpected end of input".to_owned()),
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Json::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some(c) => {
+                for (word, value) in [
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ] {
+                    if self.src[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.unexpected(c))
+            }
+        }
+    }
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(match self.peek() {
+                    Some(c) => self.unexpected(c),
+                    None => "unexpected end of input".to_owned(),
+                });
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(entries)),
+                Some(c) => return Err(self.unexpected(c)),
+                None => return Err("unexpected end of input".to_owned()),
+            }
+        }
+    }
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                Some(c) => return Err(self.unexpected(c)),
+                None => return Err("unexpected end of input".to_owned()),
+            }
+        }
+    }
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while let Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+        self.src[start..self.pos]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| format!("invalid number at column {}", start + 1))
+    }
+    fn string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                None => return Err("unterminated string".to_owned()),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.next() {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex = self.src.get(self.pos..self.pos + 4).unwrap_or_default();
+                        let code = u32::from_str_radix(hex, 16)
+                            .map_err(|_| format!("invalid escape at column {}", self.pos))?;
+                        self.pos += 4;
+                        // Surrogate pairs are rare enough in paths to not bother.
+                        out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                    }
+                    Some(c @ ('"' | '\\' | '/')) => out.push(c),
+                    Some(c) => return Err(self.unexpected(c)),
+                    None => return Err("unterminated string".to_owned()),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{write_str, write_str_array, Json};
+
+    #[test]
+    fn escapes_strings() {
+        let mut out = Vec::new();
+        write_str(&mut out, "a \"quoted\"\\path\n\u{1}é").unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"a \\\"quoted\\\"\\\\path\\n\\u0001é\""
+        );
+        let mut out = Vec::new();
+        write_str_array(&mut out, &["a", "b"]).unwrap();
+        assert_eq!(out, b"[\"a\", \"b\"]");
+    }
+    #[test]
+    fn parses_requests() {
+        let src =
+            r#" {"id": 3, "type": "changed", "path": "a\"bé", "list": [true, null, -1.5e2, {}]} "#;
+        let json = Json::parse(src).unwrap();
+        assert_eq!(json.get("id"), Some(&Json::Number(3.0)));
+        assert_eq!(json.get("path").and_then(Json::as_str), Some("a\"bé"));
+        assert_eq!(
+            json.get("list"),
+            Some(&Json::Array(vec![
+                Json::Bool(true),
+                Json::Null,
+                Json::Number(-150.0),
+                Json::Object(Vec::new())
+            ]))
+        );
+        let mut out = Vec::new();
+        json.get("id").unwrap().write(&mut out).unwrap();
+        assert_eq!(out, b"3");
+        assert!(Json::parse(r#"{"id": 1"#).is_err());
+        assert!(Json::parse(r#"{"id": 1} x"#).is_err());
+        assert!(Json::parse(r#"{id: 1}"#).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Turns a directory of markdown and MDX files into TypeScript modules for
+//! Qwik City. [`build`] runs the whole pipeline; the modules below expose the
+//! individual pieces for tools that need finer control.
+
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod date;
+pub mod diagnostics;
+pub mod html_writer;
+mod imports;
+mod interfaces;
+pub mod jobs;
+mod json;
+pub mod navigation;
+mod output;
+pub mod pipeline;
+pub mod publish;
+pub mod report;
+mod route_params;
+pub mod schema;
+pub mod serve;
+pub mod sort;
+pub mod threadpool;
+mod toml;
+pub mod types;
+mod utils;
+pub mod watch;
+pub mod yaml;
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub use crate::{
+    config::Config,
+    diagnostics::{Diagnostic, Report},
+    html_writer::Markdown,
+    types::Content,
+};
+
+use crate::{cache::Cache, threadpool::ThreadPool};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BuildOptions {
+    /// Ignore the manifest of the previous build and regenerate everything.
+    pub force: bool,
+}
+
+/// A finished build. Content problems are in `report`, the build itself
+/// only fails when it cannot read or write its directories.
+pub struct Build {
+    pub config: Arc<Config>,
+    pub content: Arc<Content>,
+    pub cache: Arc<Cache>,
+    /// Modules deleted because their source no longer exists.
+    pub removed: Vec<PathBuf>,
+    /// Files whose modules were already up to date.
+    pub unchanged: usize,
+    pub report: Report,
+}
+
+impl Build {
+    /// Whether the content had errors, or warnings with `deny_warnings`.
+    pub fn failed(&self, deny_warnings: bool) -> bool {
+        self.report.failed(deny_warnings)
+    }
+    /// Writes the JSON build report to `path`.
+    pub fn write_report(&self, path: &Path, deny_warnings: bool) -> std::io::Result<()> {
+        report::write(
+            path,
+            &self.config,
+            &self.content,
+            &self.cache,
+            &self.report,
+            deny_warnings,
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum BuildError {
+    MissingInput(PathBuf),
+    Io(PathBuf, std::io::Error),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::MissingInput(path) => {
+                write!(f, "input directory `{}` does not exist", path.display())
+            }
+            BuildError::Io(path, e) => write!(f, "`{}`: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+pub type BuildResult = Result<Build, BuildError>;
+
+/// Generates every enabled output for `config`, skipping files that did not
+/// change since the last build.
+pub fn build(config: Config) -> BuildResult {
+    build_with(config, BuildOptions::default())
+}
+
+pub fn build_with(config: Config, options: BuildOptions) -> BuildResult {
+    if !config.input.is_dir() {
+        return Err(BuildError::MissingInput(config.input));
+    }
+    let config = Arc::new(config);
+    let content = Arc::new(pipeline::load_content(&config));
+    let cache = Arc::new(Cache::new(&config, &content, options.force));
+    std::fs::create_dir_all(config.files_dir())
+        .map_err(|e| BuildError::Io(config.files_dir(), e))?;
+    // Existing modules stay in place so dev servers never see them vanish;
+    // they are overwritten only when their contents change.
+    let removed = pipeline::remove_stale_outputs(&config, &content)
+        .map_err(|e| BuildError::Io(config.files_dir(), e))?;
+    let unchanged = content
+        .tokens()
+        .iter()
+        .filter(|t| cache.is_fresh(&content, t))
+        .count();
+    let pool = ThreadPool::new(config.threads);
+    pipeline::execute_all(&pool, content.clone(), config.clone(), cache.clone());
+    let report = pool.finish();
+    cache
+        .finish(&report)
+        .map_err(|e| BuildError::Io(config.output.join(cache::MANIFEST_FILE), e))?;
+    Ok(Build {
+        config,
+        content,
+        cache,
+        removed,
+        unchanged,
+        report,
+    })
+}
@@ -1,66 +1,172 @@
-mod html_writer;
-mod imports;
-mod jobs;
-mod route_params;
-mod threadpool;
-mod types;
-mod utils;
-mod yaml;
+mod cli;
 
-use std::{
-    path::{Path, PathBuf},
-    sync::Arc,
+use std::{path::PathBuf, process::ExitCode, sync::Arc};
+
+use cli::{Args, Command};
+use qwik_city_content::{
+    check, diagnostics::Report, jobs, pipeline, serve::Server, watch::Watcher, BuildOptions, Config,
 };
 
-use threadpool::Job;
-use types::{Config, Content};
+fn main() -> ExitCode {
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let result = match args.command {
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::Version => {
+            println!("qwik-city-content {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        Command::Build => load_config(&args)
+            .map_err(Failure::from)
+            .and_then(|config| build(config, &args)),
+        Command::Clean => load_config(&args).and_then(clean).map_err(Failure::from),
+        Command::Watch => load_config(&args).and_then(watch).map_err(Failure::from),
+        Command::Serve => load_config(&args).and_then(serve).map_err(Failure::from),
+        Command::Check => load_config(&args)
+            .map_err(Failure::from)
+            .and_then(|config| check(config, &args)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Fatal(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+        Err(Failure::Content) => ExitCode::from(3),
+    }
+}
 
-use crate::threadpool::ThreadPool;
+/// Why a command did not succeed.
+enum Failure {
+    /// Nothing could be done, e.g. the config file is invalid.
+    Fatal(String),
+    /// The command ran, but content had errors, or warnings with `--deny-warnings`.
+    Content,
+}
 
-fn main() {
-    let input = PathBuf::from("examples/blog/src/content");
-    let output = PathBuf::from("examples/blog/src/content-generated");
-    let routes = PathBuf::from("examples/blog/src/routes");
-    if let Err(e) = std::fs::remove_dir_all(&output) {
-        println!("Remove Dir: {}", e);
+impl From<String> for Failure {
+    fn from(e: String) -> Self {
+        Failure::Fatal(e)
     }
-    if let Err(e) = std::fs::create_dir_all(&output.join("files")) {
-        println!("Create Dir: {}", e)
+}
+
+/// Loads the explicit or discovered config file and applies the CLI overrides.
+fn load_config(args: &Args) -> Result<Config, String> {
+    let path = match &args.config {
+        Some(path) => Some(path.clone()),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|cwd| Config::discover(&cwd)),
+    };
+    let mut config = match path {
+        Some(path) => Config::from_file(&path).map_err(|e| format!("{}: {}", path.display(), e))?,
+        None => Config::with_root(&PathBuf::new()),
+    };
+    args.apply(&mut config);
+    Ok(config)
+}
+
+fn build(config: Config, args: &Args) -> Result<(), Failure> {
+    let options = BuildOptions { force: args.force };
+    let build = qwik_city_content::build_with(config, options).map_err(|e| e.to_string())?;
+    println!(
+        "Finished in {:.3}ms",
+        build.report.elapsed.as_nanos() as f32 / 1000000.0
+    );
+    for path in build.removed.iter() {
+        println!("Removed {}", path.display());
+    }
+    println!(
+        "{} content files ({} unchanged)",
+        build.content.len(),
+        build.unchanged
+    );
+    build.report.print(&build.content);
+    if let Some(path) = &args.report {
+        build
+            .write_report(path, args.deny_warnings)
+            .map_err(|e| format!("could not write report `{}`: {}", path.display(), e))?;
+    }
+    if build.failed(args.deny_warnings) {
+        return Err(Failure::Content);
+    }
+    Ok(())
+}
+
+fn check(config: Config, args: &Args) -> Result<(), Failure> {
+    if !config.input.is_dir() {
+        return Err(Failure::Fatal(format!(
+            "input directory `{}` does not exist",
+            config.input.display()
+        )));
+    }
+    let config = Arc::new(config);
+    let content = pipeline::load_content(&config);
+    let report = Report {
+        diagnostics: check::check(&content, &config),
+        ..Default::default()
+    };
+    if report.diagnostics.is_empty() {
+        println!("Checked {} content files, no problems found", content.len());
+    }
+    report.print(&content);
+    if report.failed(args.deny_warnings) {
+        return Err(Failure::Content);
     }
-    let size = std::fs::read_dir(&input).unwrap().count();
-    let config = Arc::new(Config::new(input, output, routes));
-    let pool = ThreadPool::new(8);
-    let content = Arc::new(process_content(size, &pool, config.clone()));
+    Ok(())
+}
 
-    pool.execute(Job::ProcessCollections(content.clone(), config.clone()));
-    pool.execute(Job::ProcessTaxonomies(content.clone(), config.clone()));
-    pool.execute(Job::ProcessMarkdown(content.clone(), config.clone()));
-    pool.execute(Job::ProcessMDX(content.clone(), config.clone()));
-    pool.execute(Job::GenerateRouteParams(config.clone()));
-    if !content.is_empty() {
-        pool.execute(Job::WriteHelpers(config))
+fn watch(mut config: Config) -> Result<(), String> {
+    // Authors preview their drafts while they write.
+    config.drafts = true;
+    if !config.input.is_dir() {
+        return Err(format!(
+            "input directory `{}` does not exist",
+            config.input.display()
+        ));
     }
-    println!("{} content files", content.len())
+    Watcher::new(Arc::new(config)).run()
 }
 
-fn process_content(size: usize, pool: &ThreadPool, config: Arc<Config>) -> Content {
-    let mut content = Content::with_capacity(size);
-    process_content_rec(&config.input, &mut content, pool, config.clone());
-    content
+fn serve(mut config: Config) -> Result<(), String> {
+    config.drafts = true;
+    if !config.input.is_dir() {
+        return Err(format!(
+            "input directory `{}` does not exist",
+            config.input.display()
+        ));
+    }
+    // Stdout carries the protocol, anything meant for humans goes to stderr.
+    let stdin = std::io::stdin();
+    Server::new(Arc::new(config))
+        .run(stdin.lock(), &mut std::io::stdout().lock())
+        .map_err(|e| e.to_string())
 }
 
-fn process_content_rec(curr: &Path, content: &mut Content, pool: &ThreadPool, config: Arc<Config>) {
-    if let Ok(dir) = std::fs::read_dir(curr) {
-        for entry in dir.filter_map(|e| e.ok()) {
-            if entry.path().is_dir() && entry.path() != config.output {
-                process_content_rec(&entry.path(), content, pool, config.clone());
-            }
-            if entry.path().is_file() {
-                match std::fs::read_to_string(entry.path()) {
-                    Ok(file) => content.push_file(entry.path(), &file),
-                    Err(e) => println!("{}", e),
-                }
-            }
+fn clean(config: Config) -> Result<(), String> {
+    if let Err(e) = std::fs::remove_dir_all(&config.output) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(format!(
+                "could not remove `{}`: {}",
+                config.output.display(),
+                e
+            ));
         }
     }
+    let removed = jobs::generate_route_params::clean(&config.routes, &config.naming.route_params)
+        .map_err(|e| format!("could not clean `{}`: {}", config.routes.display(), e))?;
+    println!(
+        "Removed {} and {} route param files",
+        config.output.display(),
+        removed
+    );
+    Ok(())
 }
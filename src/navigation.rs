@@ -0,0 +1,167 @@
+use std::{collections::BTreeMap, io::Write, path::Path};
+
+use crate::{
+    config::{Config, GroupRef},
+    jobs::{write_collections, write_taxonomies, write_taxonomies::taxonomies_of},
+    types::{page_id, Content},
+};
+
+/// The pages before and after a page in its group, by index into
+/// `Content::tokens`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Neighbours {
+    pub prev: Option<usize>,
+    pub next: Option<usize>,
+}
+
+/// The neighbours of every page, indexed like `Content::tokens`. Pages are
+/// chained in the order of the group set in `config.navigation`, or else of
+/// the taxonomy of their top-level directory. Other files have none.
+pub fn neighbours(content: &Content, config: &Config) -> Vec<Neighbours> {
+    let mut neighbours = vec![Neighbours::default(); content.len()];
+    let groups: BTreeMap<String, Vec<usize>> = match &config.navigation.group {
+        // Problems with tags are reported by the collections job.
+        Some(GroupRef::Collection(tag)) => {
+            let mut collections = write_collections::collect(content, config, &mut Vec::new());
+            collections.remove_entry(tag).into_iter().collect()
+        }
+        Some(GroupRef::Taxonomy(key)) => {
+            let mut taxonomies = write_taxonomies::collect(content, config);
+            taxonomies.remove_entry(key).into_iter().collect()
+        }
+        None => write_taxonomies::collect(content, config),
+    };
+    for (key, members) in groups {
+        let pages: Vec<usize> = members
+            .into_iter()
+            .filter(|idx| {
+                let path = content.path(&content.tokens()[*idx]);
+                let top = || taxonomies_of(config, path).first() == Some(&key.as_str());
+                (path.ends_with(".md") || path.ends_with(".mdx"))
+                    && (config.navigation.group.is_some() || top())
+            })
+            .collect();
+        for (pos, idx) in pages.iter().enumerate() {
+            neighbours[*idx] = Neighbours {
+                prev: pos.checked_sub(1).map(|pos| pages[pos]),
+                next: pages.get(pos + 1).copied(),
+            };
+        }
+    }
+    neighbours
+}
+
+/// A page as `_prev` and `_next` refer to it, without importing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub id: u64,
+    pub slug: String,
+    pub title: String,
+    pub url: String,
+}
+
+impl Link {
+    /// The link to the page at `idx`. Pages without a `title` go by their slug.
+    pub fn new(content: &Content, config: &Config, idx: usize) -> Self {
+        let token = &content.tokens()[idx];
+        let path = content.path(token);
+        let input = config.input.to_string_lossy();
+        let relative = path
+            .strip_prefix(&*input)
+            .unwrap_or(path)
+            .trim_start_matches('/');
+        let id = page_id(relative);
+        let relative = Path::new(relative);
+        let slug = relative
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_owned();
+        let directory = relative
+            .parent()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let title = content
+            .frontmatter(token)
+            .ok()
+            .and_then(|yaml| yaml.string("title").map(|s| s.to_owned()))
+            .unwrap_or_else(|| slug.clone());
+        // Files at the root have no directory, nor the slash after it.
+        let template = &config.navigation.url;
+        let template = if directory.is_empty() {
+            template.replace("{directory}/", "")
+        } else {
+            template.clone()
+        };
+        let url = template
+            .replace("{directory}", directory)
+            .replace("{slug}", &slug)
+            .replace("{id}", &id.to_string());
+        Self {
+            id,
+            slug,
+            title,
+            url,
+        }
+    }
+    pub fn write_js<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_fmt(format_args!("{{ id: \"{}\", slug: ", self.id))?;
+        crate::json::write_str(w, &self.slug)?;
+        w.write_all(b", title: ")?;
+        crate::json::write_str(w, &self.title)?;
+        w.write_all(b", url: ")?;
+        crate::json::write_str(w, &self.url)?;
+        w.write_all(b" }")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{neighbours, Link, Neighbours};
+    use crate::{config::Config, types::Content};
+
+    #[test]
+    fn links_neighbours_in_order() {
+        let mut config = Config::from_toml("input = \"content\"", Path::new("")).unwrap();
+        let mut content = Content::new();
+        content.push_file("content/index.md", "---\ntitle: Home\n---\n");
+        content.push_file(
+            "content/posts/a.md",
+            "---\ntitle: A\ndate: 2022-08-21\n---\n",
+        );
+        content.push_file(
+            "content/posts/b.md",
+            "---\ntitle: B\ndate: 2022-08-22\n---\n",
+        );
+        content.push_file("content/posts/old/c.md", "---\ndate: 2022-08-20\n---\n");
+        content.push_file("content/posts/cover.png", "");
+        let all = neighbours(&content, &config);
+        let links = |n: &Neighbours| (n.prev, n.next);
+        assert_eq!(
+            all.iter().map(links).collect::<Vec<_>>(),
+            vec![
+                (None, None),
+                (Some(2), Some(3)),
+                (None, Some(1)),
+                (Some(1), None),
+                (None, None),
+            ]
+        );
+        let link = Link::new(&content, &config, 3);
+        assert_eq!((link.slug.as_str(), link.title.as_str()), ("c", "c"));
+        assert_eq!(link.url, "/posts/old/c");
+        config.navigation.url = "/{directory}/{slug}/".to_owned();
+        assert_eq!(Link::new(&content, &config, 0).url, "/index/");
+        let mut out = Vec::new();
+        Link::new(&content, &config, 1).write_js(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{{ id: \"{}\", slug: \"a\", title: \"A\", url: \"/posts/a/\" }}",
+                crate::types::page_id("posts/a.md")
+            )
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+/// First line of modules written next to files this tool does not own.
+/// Files without it were not written by this tool, so they are never removed.
+pub const HEADER: &[u8] = b"// Generated by qwik-city-content, do not edit.\n";
+
+/// Whether the file at `path` starts with [`HEADER`].
+pub fn is_generated(path: &Path) -> bool {
+    std::fs::read(path).is_ok_and(|src| src.starts_with(HEADER))
+}
+
+/// Writes `bytes` to `path` unless it already holds exactly those bytes, so
+/// dev servers only see files that really changed. The new contents go to a
+/// temporary sibling first and are renamed into place, so readers never see
+/// a partially written module. Returns whether the file was written.
+pub fn write_if_changed(path: &Path, bytes: &[u8]) -> std::io::Result<bool> {
+    if let Ok(existing) = std::fs::read(path) {
+        if existing == bytes {
+            return Ok(false);
+        }
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    std::fs::create_dir_all(dir)?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    if let Err(e) = std::fs::write(&temp, bytes).and_then(|_| std::fs::rename(&temp, path)) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok(true)
+}
+
+/// Deletes every file directly inside `dir` that is not in `keep`, returning
+/// the removed paths. A missing `dir` is not an error.
+pub fn remove_stale(dir: &Path, keep: &HashSet<PathBuf>) -> std::io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(removed),
+        Err(e) => return Err(e),
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_file() && !keep.contains(&path) {
+            std::fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+/// Removes `path` if it exists, returning whether it did.
+pub fn remove_if_exists(path: &Path) -> std::io::Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{remove_stale, write_if_changed};
+
+    #[test]
+    fn writes_only_changes_and_removes_stale() {
+        let dir = std::env::temp_dir().join(format!("qcc-output-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = dir.join("a.ts");
+        assert!(write_if_changed(&file, b"export default 1").unwrap());
+        assert!(!write_if_changed(&file, b"export default 1").unwrap());
+        assert!(write_if_changed(&file, b"export default 2").unwrap());
+        assert_eq!(std::fs::read(&file).unwrap(), b"export default 2");
+
+        let stale = dir.join("b.ts");
+        std::fs::write(&stale, "").unwrap();
+        let keep: HashSet<_> = [file.clone()].into_iter().collect();
+        assert_eq!(remove_stale(&dir, &keep).unwrap(), vec![stale]);
+        assert!(file.is_file());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
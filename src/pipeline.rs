@@ -0,0 +1,236 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::Hasher,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    cache::Cache,
+    config::Config,
+    threadpool::{Job, ThreadPool},
+    types::Content,
+};
+
+/// Reads every file below `config.input` in path order, skipping the output
+/// directory and pages that are not published.
+pub fn load_content(config: &Config) -> Content {
+    let size = std::fs::read_dir(&config.input)
+        .map(|dir| dir.count())
+        .unwrap_or_default();
+    let mut content = Content::with_capacity(size);
+    load_content_rec(&config.input, &mut content, config);
+    crate::publish::published(config, content)
+}
+
+fn load_content_rec(curr: &Path, content: &mut Content, config: &Config) {
+    if let Ok(dir) = std::fs::read_dir(curr) {
+        // The order of `read_dir` differs between machines.
+        let mut entries: Vec<std::fs::DirEntry> = dir.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.path());
+        for entry in entries {
+            if entry.path().is_dir() && entry.path() != config.output {
+                load_content_rec(&entry.path(), content, config);
+            }
+            if entry.path().is_file() {
+                match std::fs::read_to_string(entry.path()) {
+                    Ok(file) => content.push_file(entry.path(), &file),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Queues every job enabled in `config.jobs`. Work the cache marks as fresh is skipped.
+pub fn execute_all(
+    pool: &ThreadPool,
+    content: Arc<Content>,
+    config: Arc<Config>,
+    cache: Arc<Cache>,
+) {
+    let jobs = &config.jobs;
+    if jobs.collections {
+        pool.execute(Job::ProcessCollections(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+    }
+    if jobs.taxonomies {
+        pool.execute(Job::ProcessTaxonomies(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+    }
+    if jobs.schemas {
+        pool.execute(Job::WriteSchemas(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+    }
+    if jobs.json_schemas {
+        pool.execute(Job::WriteJsonSchemas(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+    }
+    pool.execute(Job::WritePages(
+        content.clone(),
+        config.clone(),
+        cache.clone(),
+    ));
+    pool.execute(Job::ValidateFrontmatter(
+        content.clone(),
+        config.clone(),
+        cache.clone(),
+    ));
+    if jobs.markdown {
+        pool.execute(Job::ProcessMarkdown(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+    }
+    if jobs.mdx {
+        pool.execute(Job::ProcessMDX(content.clone(), config.clone(), cache));
+    }
+    if jobs.route_params && config.routes.is_dir() {
+        pool.execute(Job::GenerateRouteParams(config.clone()));
+    }
+}
+
+/// Queues the jobs affected by edits to the `changed` source files. Collections
+/// and taxonomies only depend on frontmatter and file locations, so they
+/// run only when `frontmatter` differs from the hashes of `content`, which are
+/// then stored in it. Returns the cache to save once the jobs are done.
+pub fn execute_changed(
+    pool: &ThreadPool,
+    content: Arc<Content>,
+    config: Arc<Config>,
+    changed: &[PathBuf],
+    frontmatter: &mut HashMap<String, u64>,
+) -> Arc<Cache> {
+    let stored = Cache::new(&config, &content, false);
+    // Only changed files are queued, so skip the freshness checks.
+    let cache = Arc::new(stored.forced());
+    let hashes = frontmatter_hashes(&content);
+    // Frontmatter edits can reorder groups, and so change the `_prev` and
+    // `_next` of pages that were not edited. The previous manifest tells.
+    let previous = (hashes != *frontmatter).then_some(&stored);
+    for (idx, token) in content.tokens().iter().enumerate() {
+        let path = content.path(token);
+        let enabled = (path.ends_with(".md") && config.jobs.markdown)
+            || (path.ends_with(".mdx") && config.jobs.mdx);
+        let edited = changed.iter().any(|p| p.as_path() == Path::new(path));
+        let moved = || previous.is_some_and(|c| !c.is_fresh(&content, token));
+        if enabled && (edited || moved()) {
+            pool.execute(Job::ProcessFile(
+                content.clone(),
+                config.clone(),
+                idx,
+                cache.neighbours()[idx],
+            ));
+        }
+    }
+    pool.execute(Job::ValidateFrontmatter(
+        content.clone(),
+        config.clone(),
+        cache.clone(),
+    ));
+    if hashes != *frontmatter {
+        if config.jobs.collections {
+            pool.execute(Job::ProcessCollections(
+                content.clone(),
+                config.clone(),
+                cache.clone(),
+            ));
+        }
+        if config.jobs.taxonomies {
+            pool.execute(Job::ProcessTaxonomies(
+                content.clone(),
+                config.clone(),
+                cache.clone(),
+            ));
+        }
+        if config.jobs.schemas {
+            pool.execute(Job::WriteSchemas(
+                content.clone(),
+                config.clone(),
+                cache.clone(),
+            ));
+        }
+        if config.jobs.json_schemas {
+            pool.execute(Job::WriteJsonSchemas(
+                content.clone(),
+                config.clone(),
+                cache.clone(),
+            ));
+        }
+        pool.execute(Job::WritePages(
+            content.clone(),
+            config.clone(),
+            cache.clone(),
+        ));
+        *frontmatter = hashes;
+    }
+    cache
+}
+
+/// Hashes the frontmatter of every file, keyed by path.
+pub fn frontmatter_hashes(content: &Content) -> HashMap<String, u64> {
+    content
+        .tokens()
+        .iter()
+        .map(|token| {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(content.frontmatter_raw(token).as_bytes());
+            (content.path(token).to_owned(), hasher.finish())
+        })
+        .collect()
+}
+
+/// The page module generated for the source file at `path`.
+pub fn module_path(config: &Config, path: &Path) -> PathBuf {
+    let relative = path.strip_prefix(&config.input).unwrap_or(path);
+    crate::utils::output_path(config.files_dir(), &relative.to_string_lossy())
+}
+
+/// Deletes generated page modules whose source file no longer exists,
+/// returning the removed paths.
+pub fn remove_stale_outputs(config: &Config, content: &Content) -> std::io::Result<Vec<PathBuf>> {
+    let expected: HashSet<PathBuf> = content
+        .tokens()
+        .iter()
+        .map(|token| module_path(config, Path::new(content.path(token))))
+        .collect();
+    crate::output::remove_stale(&config.files_dir(), &expected)
+}
+
+/// Deletes the modules of the files at `before` that are missing from
+/// `content`, because they were removed or are no longer published.
+/// Returns the removed modules.
+pub fn remove_dropped_outputs<'a>(
+    config: &Config,
+    before: impl IntoIterator<Item = &'a str>,
+    content: &Content,
+) -> std::io::Result<Vec<PathBuf>> {
+    let loaded: HashSet<&str> = content.tokens().iter().map(|t| content.path(t)).collect();
+    let mut removed = Vec::new();
+    for path in before.into_iter().filter(|p| !loaded.contains(p)) {
+        let module = module_path(config, Path::new(path));
+        if crate::output::remove_if_exists(&module)? {
+            removed.push(module);
+        }
+    }
+    Ok(removed)
+}
+
+/// Creates the output directories if they are missing.
+pub fn prepare_output(config: &Config) -> Result<(), String> {
+    std::fs::create_dir_all(config.files_dir())
+        .map_err(|e| format!("could not create `{}`: {}", config.output.display(), e))
+}
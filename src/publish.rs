@@ -0,0 +1,137 @@
+use crate::{
+    config::Config,
+    date::Timestamp,
+    diagnostics::Diagnostic,
+    types::{Content, Token},
+};
+
+/// Whether the page is marked `draft: true`. Broken frontmatter and broken
+/// `draft` values count as published, [`validate`] reports them.
+pub fn is_draft(content: &Content, token: &Token) -> bool {
+    content
+        .frontmatter(token)
+        .map(|yaml| yaml.is_draft().unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// Whether the page belongs in a build with `config` at `now`: it is not a
+/// draft, its publish date has come and its expiry date has not.
+pub fn is_published(config: &Config, content: &Content, token: &Token, now: Timestamp) -> bool {
+    if !config.drafts && is_draft(content, token) {
+        return false;
+    }
+    let Ok(yaml) = content.frontmatter(token) else {
+        return true;
+    };
+    // Broken dates are ignored like broken `draft` values.
+    let publishing = &config.publishing;
+    let publish = yaml.date(&publishing.publish_date).ok().flatten();
+    let expiry = yaml.date(&publishing.expiry_date).ok().flatten();
+    publish.is_none_or(|date| date <= now) && expiry.is_none_or(|date| date > now)
+}
+
+/// Reports publishing fields the build could not make sense of.
+pub fn validate(config: &Config, content: &Content, token: &Token) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Ok(yaml) = content.frontmatter(token) else {
+        return diagnostics;
+    };
+    let publishing = &config.publishing;
+    let errors = [
+        ("draft", yaml.is_draft().err()),
+        (
+            &publishing.publish_date,
+            yaml.date(&publishing.publish_date).err(),
+        ),
+        (
+            &publishing.expiry_date,
+            yaml.date(&publishing.expiry_date).err(),
+        ),
+    ];
+    for (field, error) in errors {
+        if let Some(e) = error {
+            let mut diagnostic = Diagnostic::from_yaml(content, token, &e);
+            diagnostic.message = format!("invalid `{}`: {}", field, e);
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Leaves out the pages that are not published, so no job ever sees them.
+/// Pages are judged against `config.now`, or the system clock.
+pub fn published(config: &Config, content: Content) -> Content {
+    let now = config.now.unwrap_or_else(crate::date::now);
+    let keep: Vec<&Token> = content
+        .tokens()
+        .iter()
+        .filter(|t| is_published(config, &content, t, now))
+        .collect();
+    if keep.len() == content.len() {
+        return content;
+    }
+    let mut published = Content::with_capacity(keep.len());
+    for token in keep {
+        published.push_file(content.path(token), content.source(token));
+    }
+    published
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{published, validate};
+    use crate::{config::Config, types::Content};
+
+    #[test]
+    fn leaves_out_drafts() {
+        let mut config = Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new());
+        let mut content = Content::new();
+        content.push_file("a.md", "---\ntitle: A\n---\n");
+        content.push_file("b.md", "---\ntitle: B\ndraft: true\n---\n");
+        content.push_file("c.md", "---\ndraft: 1\n---\n");
+        let messages: Vec<String> = validate(&config, &content, &content.tokens()[2])
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["invalid `draft`: expected `true` or `false`"]
+        );
+        let paths = |content: &Content| -> Vec<String> {
+            content
+                .tokens()
+                .iter()
+                .map(|t| content.path(t).to_owned())
+                .collect()
+        };
+        config.drafts = true;
+        let content = published(&config, content);
+        assert_eq!(paths(&content), vec!["a.md", "b.md", "c.md"]);
+        config.drafts = false;
+        assert_eq!(paths(&published(&config, content)), vec!["a.md", "c.md"]);
+    }
+    #[test]
+    fn leaves_out_scheduled_and_expired_pages() {
+        let mut config = Config::new(PathBuf::new(), PathBuf::new(), PathBuf::new());
+        config.now = crate::date::parse("2022-08-22T12:00:00Z");
+        let mut content = Content::new();
+        content.push_file("past.md", "---\npublishDate: \"2022-08-01\"\n---\n");
+        content.push_file("future.md", "---\npublishDate: \"2022-08-23\"\n---\n");
+        content.push_file("expired.md", "---\nexpiryDate: \"2022-08-22T12:00\"\n---\n");
+        content.push_file("live.md", "---\nexpiryDate: \"2022-08-23\"\n---\n");
+        content.push_file("broken.md", "---\npublishDate: tomorrow\n---\n");
+        let messages: Vec<String> = validate(&config, &content, &content.tokens()[4])
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["invalid `publishDate`: expected a date like `2022-08-22` or `2022-08-22T10:00:00Z`"]
+        );
+        let content = published(&config, content);
+        let paths: Vec<&str> = content.tokens().iter().map(|t| content.path(t)).collect();
+        assert_eq!(paths, vec!["past.md", "live.md", "broken.md"]);
+    }
+}
@@ -0,0 +1,161 @@
+use std::{io::Write, path::Path, time::Duration};
+
+use crate::{
+    cache::Cache,
+    config::Config,
+    diagnostics::{Diagnostic, Level, Report},
+    json::{write_str, write_str_array},
+    types::Content,
+};
+
+/// Writes a JSON description of a finished build to `path`, for tools that
+/// would otherwise have to scrape stdout.
+pub fn write(
+    path: &Path,
+    config: &Config,
+    content: &Content,
+    cache: &Cache,
+    report: &Report,
+    deny_warnings: bool,
+) -> std::io::Result<()> {
+    let mut w: Vec<u8> = Vec::with_capacity(content.len() * 256);
+    w.write_all(b"{\n")?;
+    w.write_fmt(format_args!(
+        "  \"version\": \"{}\",\n",
+        env!("CARGO_PKG_VERSION")
+    ))?;
+    w.write_fmt(format_args!(
+        "  \"success\": {},\n",
+        !report.failed(deny_warnings)
+    ))?;
+    w.write_fmt(format_args!(
+        "  \"elapsedMs\": {:.3},\n",
+        millis(report.elapsed)
+    ))?;
+    write_files(&mut w, config, content, cache)?;
+
+    w.write_all(b"  \"routeParams\": ")?;
+    let route_params: Vec<String> = report
+        .jobs
+        .iter()
+        .filter(|job| job.name == "route-params")
+        .flat_map(|job| job.written.iter())
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect();
+    write_str_array(&mut w, &route_params)?;
+    w.write_all(b",\n")?;
+
+    w.write_all(b"  \"jobs\": [")?;
+    for (idx, job) in report.jobs.iter().enumerate() {
+        w.write_all(if idx == 0 { b"\n" } else { b",\n" })?;
+        w.write_fmt(format_args!(
+            "    {{ \"name\": \"{}\", \"elapsedMs\": {:.3} }}",
+            job.name,
+            millis(job.elapsed)
+        ))?;
+    }
+    w.write_all(b"\n  ],\n")?;
+
+    for (level, key, last) in [
+        (Level::Error, "errors", false),
+        (Level::Warning, "warnings", true),
+    ] {
+        w.write_fmt(format_args!("  \"{}\": [", key))?;
+        let diagnostics = report.diagnostics.iter().filter(|d| d.level == level);
+        for (idx, diagnostic) in diagnostics.enumerate() {
+            w.write_all(if idx == 0 { b"\n    " } else { b",\n    " })?;
+            write_diagnostic(&mut w, content, diagnostic)?;
+        }
+        w.write_all(if last { b"\n  ]\n" } else { b"\n  ],\n" })?;
+    }
+    w.write_all(b"}\n")?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, w)
+}
+
+fn write_files<W: Write>(
+    w: &mut W,
+    config: &Config,
+    content: &Content,
+    cache: &Cache,
+) -> std::io::Result<()> {
+    let input = config.input.to_string_lossy();
+    w.write_all(b"  \"files\": [")?;
+    for (idx, token) in content.tokens().iter().enumerate() {
+        let path = content.path(token);
+        let relative = path
+            .strip_prefix(&*input)
+            .unwrap_or(path)
+            .trim_start_matches('/');
+        let kind = if path.ends_with(".mdx") && config.jobs.mdx {
+            "mdx"
+        } else if path.ends_with(".md") && config.jobs.markdown {
+            "md"
+        } else {
+            "other"
+        };
+        w.write_all(if idx == 0 { b"\n" } else { b",\n" })?;
+        w.write_all(b"    { \"source\": ")?;
+        write_str(w, path)?;
+        w.write_all(b", \"output\": ")?;
+        if kind == "other" {
+            w.write_all(b"null")?;
+        } else {
+            let output = crate::utils::output_path(config.files_dir(), relative);
+            write_str(w, &output.to_string_lossy())?;
+        }
+        w.write_fmt(format_args!(", \"kind\": \"{}\"", kind))?;
+        w.write_fmt(format_args!(
+            ", \"unchanged\": {}",
+            kind != "other" && cache.is_fresh(content, token)
+        ))?;
+        w.write_all(b", \"collections\": ")?;
+        let collections = if config.jobs.collections {
+            crate::jobs::write_collections::tags(content, token, &mut Vec::new())
+        } else {
+            Vec::new()
+        };
+        write_str_array(w, &collections)?;
+        w.write_all(b", \"taxonomies\": ")?;
+        let taxonomies = if config.jobs.taxonomies {
+            crate::jobs::write_taxonomies::taxonomies_of(config, path)
+        } else {
+            Vec::new()
+        };
+        write_str_array(w, &taxonomies)?;
+        w.write_all(b" }")?;
+    }
+    w.write_all(b"\n  ],\n")
+}
+
+pub(crate) fn write_diagnostic<W: Write>(
+    w: &mut W,
+    content: &Content,
+    diagnostic: &Diagnostic,
+) -> std::io::Result<()> {
+    let location = content
+        .find(&diagnostic.path)
+        .and_then(|t| diagnostic.location(content.source(t)));
+    w.write_all(b"{ \"job\": ")?;
+    match diagnostic.job {
+        Some(job) => write_str(w, job)?,
+        None => w.write_all(b"null")?,
+    }
+    w.write_all(b", \"path\": ")?;
+    write_str(w, &diagnostic.path)?;
+    match location {
+        Some((line, column)) => {
+            w.write_fmt(format_args!(", \"line\": {}, \"column\": {}", line, column))?
+        }
+        None => w.write_all(b", \"line\": null, \"column\": null")?,
+    }
+    w.write_all(b", \"message\": ")?;
+    write_str(w, &diagnostic.message)?;
+    w.write_all(b" }")
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
@@ -6,7 +6,7 @@ pub struct RouteParams<'a> {
 
 impl<'a> RouteParams<'a> {
     pub fn from_path(path: &'a Path) -> Self {
-        return Self { inner: path.iter() };
+        Self { inner: path.iter() }
     }
 }
 
@@ -0,0 +1,329 @@
+use std::{fmt::Display, io::Write};
+
+use crate::{
+    config::{Config, GroupConfig},
+    diagnostics::Diagnostic,
+    types::{Content, Token},
+    yaml::{node_date, YamlKind, YamlNode},
+};
+
+/// Frontmatter fields declared for a collection or taxonomy. Fields that are
+/// not declared are left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldType,
+    pub required: bool,
+    /// Allowed values, anything of the right type when empty.
+    pub allowed: Vec<Literal>,
+    /// Written to the module of files that leave the field out.
+    pub default: Option<Literal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    /// An ISO 8601 date or timestamp.
+    Date,
+    List,
+    Object,
+}
+
+impl FieldType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" => FieldType::String,
+            "number" => FieldType::Number,
+            "integer" => FieldType::Integer,
+            "boolean" => FieldType::Boolean,
+            "date" => FieldType::Date,
+            "list" => FieldType::List,
+            "object" => FieldType::Object,
+            _ => return None,
+        })
+    }
+    /// Whether enum values and defaults can be declared for the type.
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self, FieldType::Date | FieldType::List | FieldType::Object)
+    }
+    fn matches(&self, node: &YamlNode, src: &str) -> bool {
+        match self {
+            // Dates are still strings in the generated modules.
+            FieldType::String => matches!(node.kind, YamlKind::String | YamlKind::Date),
+            FieldType::Number => node.kind == YamlKind::Number,
+            FieldType::Integer => node.kind == YamlKind::Number && !node.slice(src).contains('.'),
+            FieldType::Boolean => node.kind == YamlKind::Bool,
+            // Quoted dates are accepted, as they are for publishing.
+            FieldType::Date => match node.kind {
+                YamlKind::Date => true,
+                YamlKind::String => node_date(node, src).is_some(),
+                _ => false,
+            },
+            FieldType::List => node.kind == YamlKind::List,
+            FieldType::Object => node.kind == YamlKind::Object,
+        }
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FieldType::String => "a string",
+            FieldType::Number => "a number",
+            FieldType::Integer => "an integer",
+            FieldType::Boolean => "a boolean",
+            FieldType::Date => "a date",
+            FieldType::List => "a list",
+            FieldType::Object => "an object",
+        })
+    }
+}
+
+/// A scalar from the config file. Numbers keep their source text so the
+/// config stays hashable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    String(String),
+    Number(String),
+    Bool(bool),
+}
+
+impl Literal {
+    /// Whether the literal is a valid value for a field of type `kind`.
+    pub fn is_a(&self, kind: FieldType) -> bool {
+        match (self, kind) {
+            (Literal::String(_), FieldType::String) => true,
+            (Literal::Number(_), FieldType::Number) => true,
+            (Literal::Number(n), FieldType::Integer) => !n.contains('.'),
+            (Literal::Bool(_), FieldType::Boolean) => true,
+            _ => false,
+        }
+    }
+    /// Compares with a frontmatter value of the same type.
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            Literal::String(s) => s == unquote(value),
+            Literal::Number(n) => n.parse::<f64>().ok() == value.parse::<f64>().ok(),
+            Literal::Bool(b) => {
+                matches!((b, value), (true, "true" | "YES") | (false, "false" | "NO"))
+            }
+        }
+    }
+    pub fn write_js<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        match self {
+            Literal::String(s) => crate::json::write_str(w, s),
+            Literal::Number(n) => w.write_all(n.as_bytes()),
+            Literal::Bool(b) => w.write_fmt(format_args!("{}", b)),
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::String(s) | Literal::Number(s) => f.write_str(s),
+            Literal::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// The configured collections and taxonomies of a file that declare a schema.
+pub fn groups_of<'a>(config: &'a Config, content: &Content, token: &Token) -> Vec<&'a GroupConfig> {
+    let tags = crate::jobs::write_collections::tags(content, token, &mut Vec::new());
+    let taxonomies = crate::jobs::write_taxonomies::taxonomies_of(config, content.path(token));
+    let collections = tags.iter().filter_map(|tag| config.collection(tag));
+    let taxonomies = taxonomies.iter().filter_map(|key| config.taxonomy(key));
+    collections
+        .chain(taxonomies)
+        .filter(|group| group.schema.is_some())
+        .collect()
+}
+
+/// Checks the frontmatter of a page against the schemas of its groups.
+/// Broken frontmatter is reported elsewhere and skipped here.
+pub fn validate(config: &Config, content: &Content, token: &Token) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let path = content.path(token);
+    if !(path.ends_with(".md") || path.ends_with(".mdx")) {
+        return diagnostics;
+    }
+    let Ok(yaml) = content.frontmatter(token) else {
+        return diagnostics;
+    };
+    let raw = content.frontmatter_raw(token);
+    // Node ranges are relative to the trimmed source.
+    let trimmed = raw.len() - raw.trim_start().len();
+    let error = |node: &YamlNode, message: String| {
+        let range = node.range();
+        Diagnostic::in_frontmatter(
+            content,
+            token,
+            message,
+            range.start + trimmed..range.end + trimmed,
+        )
+    };
+    let fields = groups_of(config, content, token)
+        .into_iter()
+        .flat_map(|group| group.schema.iter().flat_map(|s| s.fields.iter()));
+    for field in fields {
+        let diagnostic = match yaml.get(&field.name) {
+            None if field.required => {
+                // Points at the opening `---`, there is nothing else to show.
+                Diagnostic::new(path, format!("missing required field `{}`", field.name))
+                    .with_span(0..3)
+            }
+            None => continue,
+            Some(node) if node.kind == YamlKind::Null => {
+                if !field.required {
+                    continue;
+                }
+                error(node, format!("`{}` is required, found null", field.name))
+            }
+            Some(node) if !field.kind.matches(node, yaml.src()) => error(
+                node,
+                format!(
+                    "`{}` must be {}, found {} `{}`",
+                    field.name,
+                    field.kind,
+                    kind_name(node.kind),
+                    node.slice(yaml.src())
+                ),
+            ),
+            Some(node) => {
+                let value = node.slice(yaml.src());
+                if field.allowed.is_empty() || field.allowed.iter().any(|v| v.matches(value)) {
+                    continue;
+                }
+                let allowed: Vec<String> =
+                    field.allowed.iter().map(|v| format!("`{}`", v)).collect();
+                error(
+                    node,
+                    format!(
+                        "`{}` must be one of {}, found `{}`",
+                        field.name,
+                        allowed.join(", "),
+                        unquote(value)
+                    ),
+                )
+            }
+        };
+        // Fields shared by several groups are only reported once.
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Defaults of the fields a page leaves out, in schema order.
+pub fn defaults<'a>(
+    config: &'a Config,
+    content: &Content,
+    token: &Token,
+) -> Vec<(&'a str, &'a Literal)> {
+    let mut defaults: Vec<(&str, &Literal)> = Vec::new();
+    let Ok(yaml) = content.frontmatter(token) else {
+        return defaults;
+    };
+    for group in groups_of(config, content, token) {
+        for field in group.schema.iter().flat_map(|s| s.fields.iter()) {
+            let Some(default) = &field.default else {
+                continue;
+            };
+            if yaml.get(&field.name).is_none() && !defaults.iter().any(|(n, _)| *n == field.name) {
+                defaults.push((&field.name, default));
+            }
+        }
+    }
+    defaults
+}
+
+fn kind_name(kind: YamlKind) -> &'static str {
+    match kind {
+        YamlKind::Object => "object",
+        YamlKind::Key => "key",
+        YamlKind::List => "list",
+        YamlKind::String => "string",
+        YamlKind::Bool => "boolean",
+        YamlKind::Number => "number",
+        YamlKind::Null => "null",
+        YamlKind::Date => "date",
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .trim_start_matches(['"', '\''])
+        .trim_end_matches(['"', '\''])
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{defaults, validate, Literal};
+    use crate::{config::Config, types::Content};
+
+    #[test]
+    fn validates_frontmatter() {
+        let src = [
+            "[collections.reviews.schema]",
+            "title = { type = \"string\", required = true }",
+            "rating = { type = \"integer\", enum = [1, 2, 3, 4, 5] }",
+            "draft = { type = \"boolean\", default = false }",
+            "published = { type = \"date\" }",
+        ]
+        .join("\n");
+        let config = Config::from_toml(&src, Path::new("")).unwrap();
+        let mut content = Content::new();
+        let ok = "---\ntitle: Fine\ntags: [reviews]\nrating: 4\npublished: 2022-08-22\n---\n";
+        content.push_file("src/content/ok.md", ok);
+        let bad = "---\ntags: [reviews]\nrating: \"five\"\npublished: soon\n---\n";
+        content.push_file("src/content/bad.md", bad);
+        content.push_file(
+            "src/content/range.md",
+            "---\ntitle: Range\ntags: [reviews]\nrating: 7\ndraft: true\n---\n",
+        );
+        content.push_file("src/content/other.md", "---\ntags: [news]\n---\n");
+        let quoted = "---\ntitle: Quoted\ntags: [reviews]\npublished: \"2022-08-22\"\n---\n";
+        content.push_file("src/content/quoted.md", quoted);
+        let messages = |idx: usize| -> Vec<String> {
+            validate(&config, &content, &content.tokens()[idx])
+                .iter()
+                .map(|d| d.message.clone())
+                .collect()
+        };
+        assert!(messages(0).is_empty());
+        assert_eq!(
+            messages(1),
+            vec![
+                "missing required field `title`",
+                "`rating` must be an integer, found string `\"five\"`",
+                "`published` must be a date, found string `soon`"
+            ]
+        );
+        assert_eq!(
+            messages(2),
+            vec!["`rating` must be one of `1`, `2`, `3`, `4`, `5`, found `7`"]
+        );
+        assert!(messages(3).is_empty());
+        assert!(messages(4).is_empty());
+        let diagnostics = validate(&config, &content, &content.tokens()[1]);
+        assert_eq!(diagnostics[1].location(bad), Some((3, 9)));
+
+        let token = &content.tokens()[0];
+        assert_eq!(
+            defaults(&config, &content, token),
+            vec![("draft", &Literal::Bool(false))]
+        );
+        assert!(defaults(&config, &content, &content.tokens()[2]).is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    cache::Cache,
+    config::Config,
+    diagnostics::{Level, Report},
+    json::{write_str, write_str_array, Json},
+    pipeline,
+    threadpool::{io_error, Job, ThreadPool},
+    types::Content,
+};
+
+/// A request read from one line of input.
+#[derive(Debug, PartialEq)]
+enum Request {
+    /// `{"type": "changed", "path": "..."}`, with an optional `content` to use
+    /// instead of reading the file.
+    Changed {
+        path: PathBuf,
+        source: Option<String>,
+    },
+    /// `{"type": "removed", "path": "..."}`
+    Removed { path: PathBuf },
+    /// `{"type": "rebuild"}` reloads everything from disk.
+    Rebuild,
+    /// `{"type": "module", "path": "..."}` returns the generated module of a file.
+    Module { path: PathBuf },
+}
+
+impl Request {
+    fn from_json(json: &Json) -> Result<Self, String> {
+        let path = || {
+            json.get("path")
+                .and_then(Json::as_str)
+                .map(PathBuf::from)
+                .ok_or_else(|| "missing `path`".to_owned())
+        };
+        match json.get("type").and_then(Json::as_str) {
+            Some("changed") => Ok(Request::Changed {
+                path: path()?,
+                source: json.get("content").and_then(Json::as_str).map(String::from),
+            }),
+            Some("removed") => Ok(Request::Removed { path: path()? }),
+            Some("rebuild") => Ok(Request::Rebuild),
+            Some("module") => Ok(Request::Module { path: path()? }),
+            Some(other) => Err(format!("unknown request type `{}`", other)),
+            None => Err("missing `type`".to_owned()),
+        }
+    }
+}
+
+/// What a request changed on disk.
+struct Outcome {
+    report: Report,
+    removed: Vec<PathBuf>,
+}
+
+/// Answers requests from a dev server plugin, one JSON object per line.
+/// Content stays in memory between requests, so a change only re-runs the
+/// jobs it affects.
+pub struct Server {
+    config: Arc<Config>,
+    /// Source of every content file, including unsaved edits sent with
+    /// requests. Kept in the order they were loaded in, so modules listing
+    /// every file do not change when one of them is edited.
+    files: Vec<(PathBuf, String)>,
+    content: Arc<Content>,
+    frontmatter: HashMap<String, u64>,
+}
+
+impl Server {
+    pub fn new(config: Arc<Config>) -> Self {
+        Self {
+            config,
+            files: Vec::new(),
+            content: Arc::new(Content::new()),
+            frontmatter: HashMap::new(),
+        }
+    }
+    /// Builds everything, announces it with a `ready` line, then answers one
+    /// request per line of `input` until it is closed.
+    pub fn run<R: BufRead, W: Write>(mut self, input: R, output: &mut W) -> std::io::Result<()> {
+        let outcome = self.rebuild();
+        let mut line = b"{\"event\": \"ready\", ".to_vec();
+        self.write_outcome(&mut line, &outcome)?;
+        send(output, &line)?;
+        for request in input.lines() {
+            let request = request?;
+            if request.trim().is_empty() {
+                continue;
+            }
+            let reply = self.handle(&request)?;
+            send(output, &reply)?;
+        }
+        Ok(())
+    }
+    /// Answers a single request. Failures are reported to the client, only
+    /// errors building the reply itself are returned.
+    pub fn handle(&mut self, request: &str) -> std::io::Result<Vec<u8>> {
+        let json = Json::parse(request);
+        let id = match &json {
+            Ok(json) => json.get("id").cloned().unwrap_or(Json::Null),
+            Err(_) => Json::Null,
+        };
+        let mut w = b"{\"id\": ".to_vec();
+        id.write(&mut w)?;
+        w.write_all(b", ")?;
+        let request = json.and_then(|json| Request::from_json(&json));
+        let result = request.and_then(|request| match request {
+            Request::Changed { path, source } => self.changed(&path, source).map(Some),
+            Request::Removed { path } => self.removed(&path).map(Some),
+            Request::Rebuild => Ok(Some(self.rebuild())),
+            Request::Module { path } => {
+                self.write_module(&mut w, &path)?;
+                Ok(None)
+            }
+        });
+        match result {
+            Ok(Some(outcome)) => self.write_outcome(&mut w, &outcome)?,
+            Ok(None) => {}
+            Err(e) => {
+                w.write_all(b"\"ok\": false, \"error\": ")?;
+                write_str(&mut w, &e)?;
+                w.write_all(b"}")?;
+            }
+        }
+        Ok(w)
+    }
+    /// Reloads every file from disk and regenerates all outputs.
+    fn rebuild(&mut self) -> Outcome {
+        let config = self.config.clone();
+        let content = pipeline::load_content(&config);
+        self.files = content
+            .tokens()
+            .iter()
+            .map(|t| (PathBuf::from(content.path(t)), content.source(t).to_owned()))
+            .collect();
+        self.content = Arc::new(content);
+        self.frontmatter = pipeline::frontmatter_hashes(&self.content);
+        let removed = std::fs::create_dir_all(config.files_dir())
+            .and_then(|()| pipeline::remove_stale_outputs(&config, &self.content));
+        // Modules are only written when they change, so forcing every job
+        // still only reports the modules that are actually different.
+        let cache = Arc::new(Cache::new(&config, &self.content, true));
+        let pool = ThreadPool::new(config.threads);
+        pipeline::execute_all(&pool, self.content.clone(), config.clone(), cache.clone());
+        let mut report = pool.finish();
+        let removed = match removed {
+            Ok(removed) => removed,
+            Err(e) => {
+                report.diagnostics.push(io_error(&config.files_dir(), e));
+                Vec::new()
+            }
+        };
+        self.finish_cache(&cache, &mut report);
+        Outcome { report, removed }
+    }
+    fn changed(&mut self, path: &Path, source: Option<String>) -> Result<Outcome, String> {
+        let path = self.resolve(path);
+        if self.is_route(&path) {
+            return Ok(self.routes_changed());
+        }
+        if !path.starts_with(&self.config.input) {
+            return Err(self.outside(&path));
+        }
+        let source = match source {
+            Some(source) => source,
+            None => std::fs::read_to_string(&path)
+                .map_err(|e| format!("could not read `{}`: {}", path.display(), e))?,
+        };
+        // Kept in path order, like a fresh build loads them.
+        match self.files.binary_search_by(|(p, _)| p.cmp(&path)) {
+            Ok(idx) => self.files[idx].1 = source,
+            Err(idx) => self.files.insert(idx, (path.clone(), source)),
+        }
+        Ok(self.update(&[path]))
+    }
+    fn removed(&mut self, path: &Path) -> Result<Outcome, String> {
+        let path = self.resolve(path);
+        if self.is_route(&path) {
+            return Ok(self.routes_changed());
+        }
+        if !path.starts_with(&self.config.input) {
+            return Err(self.outside(&path));
+        }
+        if let Some(idx) = self.files.iter().position(|(p, _)| *p == path) {
+            self.files.remove(idx);
+        }
+        Ok(self.update(&[]))
+    }
+    /// Rebuilds `Content` from the files in memory, removes the modules of
+    /// files that dropped out of it and re-runs the jobs affected by `changed`.
+    fn update(&mut self, changed: &[PathBuf]) -> Outcome {
+        let mut content = Content::with_capacity(self.files.len());
+        for (path, source) in self.files.iter() {
+            content.push_file(path, source);
+        }
+        let content = Arc::new(crate::publish::published(&self.config, content));
+        let before = std::mem::replace(&mut self.content, content);
+        let before = before.tokens().iter().map(|t| before.path(t));
+        let removed = pipeline::remove_dropped_outputs(&self.config, before, &self.content);
+        let pool = ThreadPool::new(self.config.threads);
+        let cache = pipeline::execute_changed(
+            &pool,
+            self.content.clone(),
+            self.config.clone(),
+            changed,
+            &mut self.frontmatter,
+        );
+        let mut report = pool.finish();
+        let removed = match removed {
+            Ok(removed) => removed,
+            Err(e) => {
+                report
+                    .diagnostics
+                    .push(io_error(&self.config.files_dir(), e));
+                Vec::new()
+            }
+        };
+        self.finish_cache(&cache, &mut report);
+        Outcome { report, removed }
+    }
+    fn routes_changed(&self) -> Outcome {
+        let pool = ThreadPool::new(1);
+        if self.config.jobs.route_params && self.config.routes.is_dir() {
+            pool.execute(Job::GenerateRouteParams(self.config.clone()));
+        }
+        Outcome {
+            report: pool.finish(),
+            removed: Vec::new(),
+        }
+    }
+    fn finish_cache(&self, cache: &Cache, report: &mut Report) {
+        if let Err(e) = cache.finish(report) {
+            let manifest = self.config.output.join(crate::cache::MANIFEST_FILE);
+            report.diagnostics.push(io_error(&manifest, e));
+        }
+    }
+    fn write_module<W: Write>(&self, w: &mut W, path: &Path) -> Result<(), String> {
+        let path = self.resolve(path);
+        if !self.files.iter().any(|(p, _)| *p == path) {
+            return Err(format!("`{}` is not a content file", path.display()));
+        }
+        let module = pipeline::module_path(&self.config, &path);
+        let code = std::fs::read_to_string(&module)
+            .map_err(|e| format!("could not read `{}`: {}", module.display(), e))?;
+        let write = |w: &mut W| -> std::io::Result<()> {
+            w.write_all(b"\"ok\": true, \"module\": ")?;
+            write_str(w, &module.to_string_lossy())?;
+            w.write_all(b", \"code\": ")?;
+            write_str(w, &code)?;
+            w.write_all(b"}")
+        };
+        write(w).map_err(|e| e.to_string())
+    }
+    fn write_outcome<W: Write>(&self, w: &mut W, outcome: &Outcome) -> std::io::Result<()> {
+        let report = &outcome.report;
+        let paths = |paths: &mut dyn Iterator<Item = &PathBuf>| -> Vec<String> {
+            paths.map(|p| p.to_string_lossy().into_owned()).collect()
+        };
+        w.write_fmt(format_args!(
+            "\"ok\": {}, \"modules\": ",
+            !report.failed(false)
+        ))?;
+        write_str_array(
+            w,
+            &paths(&mut report.jobs.iter().flat_map(|j| j.written.iter())),
+        )?;
+        w.write_all(b", \"removed\": ")?;
+        write_str_array(w, &paths(&mut outcome.removed.iter()))?;
+        for (level, key) in [(Level::Error, "errors"), (Level::Warning, "warnings")] {
+            w.write_fmt(format_args!(", \"{}\": [", key))?;
+            let diagnostics = report.diagnostics.iter().filter(|d| d.level == level);
+            for (idx, diagnostic) in diagnostics.enumerate() {
+                if idx > 0 {
+                    w.write_all(b", ")?;
+                }
+                crate::report::write_diagnostic(w, &self.content, diagnostic)?;
+            }
+            w.write_all(b"]")?;
+        }
+        w.write_all(b"}")
+    }
+    /// Makes `path` comparable with the configured directories, which are
+    /// absolute when they come from a config file and relative otherwise.
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let Ok(cwd) = std::env::current_dir() else {
+            return path.to_path_buf();
+        };
+        match (path.is_absolute(), self.config.input.is_absolute()) {
+            (true, false) => path.strip_prefix(&cwd).unwrap_or(path).to_path_buf(),
+            (false, true) => cwd.join(path),
+            _ => path.to_path_buf(),
+        }
+    }
+    fn is_route(&self, path: &Path) -> bool {
+        path.starts_with(&self.config.routes) && !path.starts_with(&self.config.input)
+    }
+    fn outside(&self, path: &Path) -> String {
+        format!(
+            "`{}` is outside of `{}` and `{}`",
+            path.display(),
+            self.config.input.display(),
+            self.config.routes.display()
+        )
+    }
+}
+
+fn send<W: Write>(w: &mut W, line: &[u8]) -> std::io::Result<()> {
+    w.write_all(line)?;
+    w.write_all(b"\n")?;
+    w.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, sync::Arc};
+
+    use super::{Request, Server};
+    use crate::{config::Config, json::Json};
+
+    #[test]
+    fn parses_requests() {
+        let parse = |src: &str| Request::from_json(&Json::parse(src).unwrap());
+        assert_eq!(
+            parse(r#"{"id": 1, "type": "changed", "path": "a.md", "content": "A"}"#),
+            Ok(Request::Changed {
+                path: PathBuf::from("a.md"),
+                source: Some("A".to_owned())
+            })
+        );
+        assert_eq!(parse(r#"{"type": "rebuild"}"#), Ok(Request::Rebuild));
+        assert_eq!(
+            parse(r#"{"type": "module"}"#),
+            Err("missing `path`".to_owned())
+        );
+        assert_eq!(
+            parse(r#"{"type": "deploy"}"#),
+            Err("unknown request type `deploy`".to_owned())
+        );
+    }
+    #[test]
+    fn removes_modules_of_unpublished_pages() {
+        let root = std::env::temp_dir().join(format!("qcc-serve-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let post = root.join("content/post.md");
+        std::fs::create_dir_all(post.parent().unwrap()).unwrap();
+        std::fs::write(&post, "---\ntitle: Post\n---\n").unwrap();
+        let mut config = Config::new(root.join("content"), root.join("out"), root.join("routes"));
+        config.threads = 1;
+        let mut server = Server::new(Arc::new(config));
+        server.rebuild();
+        let module = root.join("out/files/post.md.ts");
+        assert!(module.is_file());
+        let request = format!(
+            r#"{{"type": "changed", "path": "{}", "content": "---\ndraft: true\n---\n"}}"#,
+            post.display()
+        );
+        let reply = String::from_utf8(server.handle(&request).unwrap()).unwrap();
+        assert!(reply.contains(&format!("\"removed\": [\"{}\"]", module.display())));
+        assert!(!module.exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
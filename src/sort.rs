@@ -0,0 +1,168 @@
+use std::cmp::Ordering;
+
+use crate::{date::Timestamp, types::Content, yaml::YamlKind};
+
+/// A frontmatter field pages are ordered by, written `date` or `-date` for
+/// descending order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: String,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub fn parse(src: &str) -> Option<Self> {
+        let (field, descending) = match src.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (src, false),
+        };
+        if field.is_empty() || field.starts_with('-') {
+            return None;
+        }
+        Some(Self {
+            field: field.to_owned(),
+            descending,
+        })
+    }
+    /// Newest first, then by title.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                field: "date".to_owned(),
+                descending: true,
+            },
+            Self {
+                field: "title".to_owned(),
+                descending: false,
+            },
+        ]
+    }
+}
+
+/// A frontmatter value pages can be compared by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    Bool(bool),
+    Number(f64),
+    Date(Timestamp),
+    String(&'a str),
+}
+
+impl Value<'_> {
+    /// Values of different kinds are ordered by kind.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Bool(_) => 0,
+            Value::Number(_) => 1,
+            Value::Date(_) => 2,
+            Value::String(_) => 3,
+        }
+    }
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.cmp(b),
+            // Case only breaks ties, so `apple` comes before `Banana`.
+            (Value::String(a), Value::String(b)) => {
+                let lower = |s: &str| s.chars().flat_map(char::to_lowercase).collect::<String>();
+                lower(a).cmp(&lower(b)).then_with(|| a.cmp(b))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Sorts the pages at `members`, indices into `Content::tokens`, by `keys`.
+/// Pages without a value for a key come after the ones that have it, and
+/// ties keep their order in `members`.
+pub fn sort(content: &Content, members: &mut [usize], keys: &[SortKey]) {
+    if keys.is_empty() {
+        return;
+    }
+    let mut keyed: Vec<(usize, Vec<Option<Value>>)> = members
+        .iter()
+        .map(|idx| (*idx, values(content, *idx, keys)))
+        .collect();
+    keyed.sort_by(|(_, a), (_, b)| {
+        for (key, (a, b)) in keys.iter().zip(a.iter().zip(b.iter())) {
+            let ordering = match (a, b) {
+                (Some(a), Some(b)) if key.descending => b.compare(a),
+                (Some(a), Some(b)) => a.compare(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+    for (member, (idx, _)) in members.iter_mut().zip(keyed) {
+        *member = idx;
+    }
+}
+
+fn values<'a>(content: &'a Content, idx: usize, keys: &[SortKey]) -> Vec<Option<Value<'a>>> {
+    let yaml = content
+        .tokens()
+        .get(idx)
+        .and_then(|token| content.frontmatter(token).ok());
+    keys.iter()
+        .map(|key| {
+            let yaml = yaml.as_ref()?;
+            let node = yaml.get(&key.field)?;
+            let value = node
+                .slice(yaml.src())
+                .trim_start_matches(['"', '\''])
+                .trim_end_matches(['"', '\'']);
+            match node.kind {
+                YamlKind::Bool => Some(Value::Bool(matches!(value, "true" | "YES"))),
+                YamlKind::Number => value.parse().ok().map(Value::Number),
+                YamlKind::Date => crate::date::parse(value).map(Value::Date),
+                // Quoted dates are strings, but still sort by time.
+                YamlKind::String => Some(
+                    crate::date::parse(value)
+                        .map(Value::Date)
+                        .unwrap_or(Value::String(value)),
+                ),
+                YamlKind::Null | YamlKind::Key | YamlKind::List | YamlKind::Object => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sort, SortKey};
+    use crate::types::Content;
+
+    #[test]
+    fn sorts_by_keys() {
+        let mut content = Content::new();
+        content.push_file("a.md", "---\ntitle: banana\ndate: 2022-08-21\n---\n");
+        content.push_file("b.md", "---\ntitle: Cherry\ndate: 2022-08-22\n---\n");
+        content.push_file("c.md", "---\ntitle: apple\ndate: 2022-08-21\n---\n");
+        content.push_file("d.md", "---\ntitle: undated\n---\n");
+        content.push_file("e.md", "---\ntitle: \"open\n---\n");
+        let mut members = vec![0, 1, 2, 3, 4];
+        sort(&content, &mut members, &SortKey::defaults());
+        assert_eq!(members, vec![1, 2, 0, 3, 4]);
+        let keys = [SortKey::parse("-title").unwrap()];
+        sort(&content, &mut members, &keys);
+        assert_eq!(members, vec![3, 1, 0, 2, 4]);
+        assert_eq!(SortKey::parse("--date"), None);
+    }
+    #[test]
+    fn sorts_quoted_and_unquoted_dates_by_time() {
+        let mut content = Content::new();
+        content.push_file("a.md", "---\ndate: 2022-08-21\n---\n");
+        content.push_file("b.md", "---\ndate: \"2022-08-23\"\n---\n");
+        content.push_file("c.md", "---\ndate: 2022-08-22T10:00Z\n---\n");
+        content.push_file("d.md", "---\ndate: \"2022-08-20\"\n---\n");
+        let mut members = vec![0, 1, 2, 3];
+        sort(&content, &mut members, &SortKey::defaults());
+        assert_eq!(members, vec![1, 2, 0, 3]);
+    }
+}
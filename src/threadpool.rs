@@ -1,4 +1,5 @@
 use std::{
+    path::Path,
     sync::{
         mpsc::{Receiver, Sender},
         Arc, Mutex,
@@ -7,11 +8,19 @@ use std::{
     time::Instant,
 };
 
-use crate::types::{Config, Content};
+use crate::{
+    cache::Cache,
+    config::Config,
+    diagnostics::{Diagnostic, JobSummary, Report},
+    jobs::JobOutput,
+    navigation::Neighbours,
+    types::Content,
+};
 
 pub struct ThreadPool {
     start: Instant,
     sender: Sender<Job>,
+    results: Receiver<JobResult>,
     workers: Vec<Worker>,
 }
 
@@ -20,36 +29,56 @@ impl ThreadPool {
         assert!(size > 0);
         let mut workers = Vec::with_capacity(size);
         let (sender, receiver) = std::sync::mpsc::channel::<Job>();
+        let (results_sender, results) = std::sync::mpsc::channel();
         let receiver = Arc::new(Mutex::new(receiver));
         for id in 0..size {
-            workers.push(Worker::new(id, Arc::clone(&receiver)))
+            workers.push(Worker::new(
+                id,
+                Arc::clone(&receiver),
+                results_sender.clone(),
+            ))
         }
         Self {
             start: Instant::now(),
             sender,
+            results,
             workers,
         }
     }
     pub fn execute(&self, job: Job) {
         self.sender.send(job).unwrap()
     }
-}
-
-impl Drop for ThreadPool {
-    fn drop(&mut self) {
-        for _ in &mut self.workers {
+    /// Waits for every queued job and collects what they reported.
+    pub fn finish(mut self) -> Report {
+        self.shutdown();
+        let mut report = Report {
+            elapsed: self.start.elapsed(),
+            ..Report::default()
+        };
+        for result in self.results.try_iter() {
+            report.diagnostics.extend(result.diagnostics);
+            report.jobs.push(result.summary);
+        }
+        report
+    }
+    fn shutdown(&mut self) {
+        if self.workers.is_empty() {
+            return;
+        }
+        for _ in &self.workers {
             self.sender.send(Job::Terminate).unwrap();
         }
-        for worker in &mut self.workers {
-            // println!("Shutting down worker {}", worker.id);
+        for mut worker in self.workers.drain(..) {
             if let Some(thread) = worker.thread.take() {
                 thread.join().unwrap();
             }
         }
-        println!(
-            "Finished in {:.3}ms",
-            self.start.elapsed().as_nanos() as f32 / 1000000.0
-        )
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shutdown()
     }
 }
 
@@ -61,43 +90,95 @@ struct Worker {
 }
 
 impl Worker {
-    fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>) -> Self {
+    fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>, results: Sender<JobResult>) -> Self {
         let thread = std::thread::spawn(move || loop {
             let job = receiver.lock().unwrap().recv().unwrap();
-            // println!("Worker {} got a job; executing.", id);
-            match job {
-                Job::WriteHelpers(config) => {
-                    if let Err(e) = crate::jobs::write_type_helpers::generate(config) {
-                        println!("Helpers {}", e)
-                    }
-                }
+            let start = Instant::now();
+            let (name, mut output) = match job {
                 Job::GenerateRouteParams(config) => {
-                    if let Err(e) = crate::jobs::generate_route_params::generate(&config.routes) {
-                        println!("Params {}", e)
-                    }
-                }
-                Job::ProcessCollections(content, config) => {
-                    if crate::jobs::write_collections::process_all(content, config).is_err() {
-                        println!("Yaml Error error");
-                    }
-                }
-                Job::ProcessTaxonomies(content, config) => {
-                    crate::jobs::write_taxonomies::process_all(content, config)
-                }
-                Job::ProcessMarkdown(content, config) => {
-                    if let Err(e) = crate::jobs::process_markdown::process_all(content, config) {
-                        println!("Markdown {}", e)
+                    let mut output = JobOutput::default();
+                    match crate::jobs::generate_route_params::generate(
+                        &config.routes,
+                        &config.naming.route_params,
+                    ) {
+                        Ok(written) => output.written = written,
+                        Err(e) => output.diagnostics.push(io_error(&config.routes, e)),
                     }
+                    ("route-params", output)
                 }
-                Job::ProcessMDX(content, config) => {
-                    if let Err(e) = crate::jobs::process_mdx::process_all(content, config) {
-                        println!("Markdown {}", e)
+                Job::ProcessCollections(content, config, cache) => (
+                    "collections",
+                    crate::jobs::write_collections::process_all(content, config, cache),
+                ),
+                Job::ProcessTaxonomies(content, config, cache) => (
+                    "taxonomies",
+                    crate::jobs::write_taxonomies::process_all(content, config, cache),
+                ),
+                Job::WriteSchemas(content, config, cache) => (
+                    "schemas",
+                    crate::jobs::write_schemas::process_all(content, config, cache),
+                ),
+                Job::WriteJsonSchemas(content, config, cache) => (
+                    "json-schemas",
+                    crate::jobs::write_json_schemas::process_all(content, config, cache),
+                ),
+                Job::WritePages(content, config, cache) => (
+                    "pages",
+                    crate::jobs::write_pages::process_all(content, config, cache),
+                ),
+                Job::ValidateFrontmatter(content, config, cache) => (
+                    "frontmatter",
+                    crate::jobs::validate_frontmatter::process_all(content, config, cache),
+                ),
+                Job::ProcessMarkdown(content, config, cache) => (
+                    "markdown",
+                    crate::jobs::process_markdown::process_all(content, config, cache),
+                ),
+                Job::ProcessMDX(content, config, cache) => (
+                    "mdx",
+                    crate::jobs::process_mdx::process_all(content, config, cache),
+                ),
+                Job::ProcessFile(content, config, idx, neighbours) => {
+                    let token = &content.tokens()[idx];
+                    let path = content.path(token);
+                    let (name, result) = if path.ends_with(".mdx") {
+                        (
+                            "mdx",
+                            crate::jobs::process_mdx::process_file(
+                                &content, token, &config, neighbours,
+                            ),
+                        )
+                    } else {
+                        (
+                            "markdown",
+                            crate::jobs::process_markdown::process_file(
+                                &content, token, &config, neighbours,
+                            ),
+                        )
+                    };
+                    let mut output = JobOutput::default();
+                    match result {
+                        Ok(written) => output.written.extend(written),
+                        Err(e) => output.diagnostics.push(io_error(Path::new(path), e)),
                     }
+                    (name, output)
                 }
                 Job::Terminate => {
                     break;
                 }
+            };
+            for diagnostic in output.diagnostics.iter_mut() {
+                diagnostic.job = Some(name);
             }
+            // The pool outlives its workers, so the receiver is still there.
+            let _ = results.send(JobResult {
+                summary: JobSummary {
+                    name,
+                    elapsed: start.elapsed(),
+                    written: output.written,
+                },
+                diagnostics: output.diagnostics,
+            });
         });
         Self {
             id,
@@ -106,12 +187,28 @@ impl Worker {
     }
 }
 
+struct JobResult {
+    summary: JobSummary,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A job failure that is not tied to a position in a content file.
+pub fn io_error(path: &Path, error: std::io::Error) -> Diagnostic {
+    Diagnostic::new(path.display().to_string(), error.to_string())
+}
+
 pub enum Job {
     GenerateRouteParams(Arc<Config>),
-    WriteHelpers(Arc<Config>),
-    ProcessCollections(Arc<Content>, Arc<Config>),
-    ProcessTaxonomies(Arc<Content>, Arc<Config>),
-    ProcessMarkdown(Arc<Content>, Arc<Config>),
-    ProcessMDX(Arc<Content>, Arc<Config>),
+    ProcessCollections(Arc<Content>, Arc<Config>, Arc<Cache>),
+    ProcessTaxonomies(Arc<Content>, Arc<Config>, Arc<Cache>),
+    WriteSchemas(Arc<Content>, Arc<Config>, Arc<Cache>),
+    WriteJsonSchemas(Arc<Content>, Arc<Config>, Arc<Cache>),
+    WritePages(Arc<Content>, Arc<Config>, Arc<Cache>),
+    ValidateFrontmatter(Arc<Content>, Arc<Config>, Arc<Cache>),
+    ProcessMarkdown(Arc<Content>, Arc<Config>, Arc<Cache>),
+    ProcessMDX(Arc<Content>, Arc<Config>, Arc<Cache>),
+    /// Renders a single `.md` or `.mdx` file, by index into `Content::tokens`,
+    /// with its neighbours as computed once for the whole batch.
+    ProcessFile(Arc<Content>, Arc<Config>, usize, Neighbours),
     Terminate,
 }
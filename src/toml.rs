@@ -0,0 +1,411 @@
+use std::{fmt::Display, iter::Peekable, str::CharIndices};
+
+/// The subset of TOML used by `qwik-content.toml`: tables, dotted table
+/// headers, strings, integers, floats, booleans, arrays and inline tables.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "boolean",
+            Value::Array(_) => "array",
+            Value::Table(_) => "table",
+        }
+    }
+}
+
+/// Key/value pairs in declaration order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    entries: Vec<(String, Value)>,
+}
+
+impl Table {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+    /// Returns the sub-table at `path`, creating any missing tables on the way.
+    fn table_at(&mut self, path: &[String]) -> Result<&mut Table, String> {
+        let Some((first, rest)) = path.split_first() else {
+            return Ok(self);
+        };
+        if self.get(first).is_none() {
+            self.entries
+                .push((first.clone(), Value::Table(Table::default())));
+        }
+        match self.get_mut(first) {
+            Some(Value::Table(table)) => table.table_at(rest),
+            _ => Err(format!("`{}` is not a table", first)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TomlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub struct Parser<'a> {
+    src: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn from_str(src: &'a str) -> Self {
+        Self {
+            src,
+            chars: src.char_indices().peekable(),
+            line: 1,
+        }
+    }
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+    fn chomp(&mut self) -> Option<char> {
+        let next = self.chars.next().map(|(_, c)| c);
+        if next == Some('\n') {
+            self.line += 1;
+        }
+        next
+    }
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map(|(i, _)| *i).unwrap_or(self.src.len())
+    }
+    fn error<T, S: Into<String>>(&self, message: S) -> Result<T, TomlError> {
+        Err(TomlError {
+            line: self.line,
+            message: message.into(),
+        })
+    }
+    fn expect(&mut self, expected: char) -> Result<(), TomlError> {
+        match self.chomp() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => self.error(format!("expected `{}`, found `{}`", expected, c)),
+            None => self.error(format!("expected `{}`, found end of input", expected)),
+        }
+    }
+    fn skip_ws(&mut self) {
+        while let Some(' ') | Some('\t') = self.peek() {
+            self.chomp();
+        }
+    }
+    /// Skips whitespace, newlines and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\r') | Some('\n') => {
+                    self.chomp();
+                }
+                Some('#') => self.skip_comment(),
+                _ => break,
+            }
+        }
+    }
+    fn skip_comment(&mut self) {
+        while !matches!(self.peek(), Some('\n') | None) {
+            self.chomp();
+        }
+    }
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('#') => self.skip_comment(),
+            Some('\r') | Some('\n') | None => {}
+            Some(c) => return self.error(format!("unexpected `{}` after value", c)),
+        }
+        Ok(())
+    }
+    pub fn parse(mut self) -> Result<Table, TomlError> {
+        let mut root = Table::default();
+        let mut path: Vec<String> = Vec::new();
+        let mut headers: Vec<Vec<String>> = Vec::new();
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None => break,
+                Some('[') => {
+                    self.chomp();
+                    path = self.parse_key_path(']')?;
+                    self.expect(']')?;
+                    self.end_of_line()?;
+                    if headers.contains(&path) {
+                        return self.error(format!("duplicate table `[{}]`", path.join(".")));
+                    }
+                    headers.push(path.clone());
+                    let line = self.line;
+                    root.table_at(&path)
+                        .map_err(|message| TomlError { line, message })?;
+                }
+                Some(_) => {
+                    let key = self.parse_key()?;
+                    self.skip_ws();
+                    self.expect('=')?;
+                    self.skip_ws();
+                    let value = self.parse_value()?;
+                    self.end_of_line()?;
+                    let line = self.line;
+                    let table = root
+                        .table_at(&path)
+                        .map_err(|message| TomlError { line, message })?;
+                    if table.get(&key).is_some() {
+                        return self.error(format!("duplicate key `{}`", key));
+                    }
+                    table.entries.push((key, value));
+                }
+            }
+        }
+        Ok(root)
+    }
+    fn parse_key_path(&mut self, end: char) -> Result<Vec<String>, TomlError> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_ws();
+            path.push(self.parse_key()?);
+            self.skip_ws();
+            match self.peek() {
+                Some('.') => {
+                    self.chomp();
+                }
+                Some(c) if c == end => return Ok(path),
+                _ => return self.error(format!("expected `.` or `{}` in table header", end)),
+            }
+        }
+    }
+    fn parse_key(&mut self) -> Result<String, TomlError> {
+        match self.peek() {
+            Some('"') | Some('\'') => self.parse_string(),
+            _ => {
+                let start = self.offset();
+                while let Some(c) = self.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        break;
+                    }
+                    self.chomp();
+                }
+                let end = self.offset();
+                if start == end {
+                    return self.error("expected a key");
+                }
+                Ok(self.src[start..end].to_owned())
+            }
+        }
+    }
+    fn parse_value(&mut self) -> Result<Value, TomlError> {
+        match self.peek() {
+            None => self.error("expected a value, found end of input"),
+            Some('"') | Some('\'') => Ok(Value::String(self.parse_string()?)),
+            Some('[') => self.parse_array(),
+            Some('{') => self.parse_inline_table(),
+            Some(_) => self.parse_scalar(),
+        }
+    }
+    fn parse_string(&mut self) -> Result<String, TomlError> {
+        let quote = self.chomp().unwrap_or('"');
+        let mut string = String::new();
+        loop {
+            if let None | Some('\n') = self.peek() {
+                return self.error("unterminated string");
+            }
+            match self.chomp() {
+                Some(c) if c == quote => return Ok(string),
+                Some('\\') if quote == '"' => match self.chomp() {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some('r') => string.push('\r'),
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some(c) => return self.error(format!("unknown escape `\\{}`", c)),
+                    None => return self.error("unterminated string"),
+                },
+                Some(c) => string.push(c),
+                None => return self.error("unterminated string"),
+            }
+        }
+    }
+    fn parse_array(&mut self) -> Result<Value, TomlError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            if let Some(']') = self.peek() {
+                self.chomp();
+                return Ok(Value::Array(items));
+            }
+            items.push(self.parse_value()?);
+            self.skip_trivia();
+            match self.peek() {
+                Some(',') => {
+                    self.chomp();
+                }
+                Some(']') => {}
+                _ => return self.error("expected `,` or `]` in array"),
+            }
+        }
+    }
+    fn parse_inline_table(&mut self) -> Result<Value, TomlError> {
+        self.expect('{')?;
+        let mut table = Table::default();
+        loop {
+            self.skip_ws();
+            if let Some('}') = self.peek() {
+                self.chomp();
+                return Ok(Value::Table(table));
+            }
+            let key = self.parse_key()?;
+            self.skip_ws();
+            self.expect('=')?;
+            self.skip_ws();
+            let value = self.parse_value()?;
+            if table.get(&key).is_some() {
+                return self.error(format!("duplicate key `{}`", key));
+            }
+            table.entries.push((key, value));
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => {
+                    self.chomp();
+                }
+                Some('}') => {}
+                _ => return self.error("expected `,` or `}` in inline table"),
+            }
+        }
+    }
+    fn parse_scalar(&mut self) -> Result<Value, TomlError> {
+        let start = self.offset();
+        while let Some(c) = self.peek() {
+            if matches!(c, ',' | ']' | '}' | '#' | ' ' | '\t' | '\r' | '\n') {
+                break;
+            }
+            self.chomp();
+        }
+        let end = self.offset();
+        let slice = &self.src[start..end];
+        match slice {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => {
+                let digits = slice.replace('_', "");
+                // Rust also reads `inf` and `nan`, which are left out.
+                let numeric = digits
+                    .trim_start_matches(['+', '-'])
+                    .starts_with(|c: char| c.is_ascii_digit());
+                if !numeric {
+                    self.error(format!("invalid value `{}`", slice))
+                } else if let Ok(n) = digits.parse::<i64>() {
+                    Ok(Value::Integer(n))
+                } else if let Ok(n) = digits.parse::<f64>() {
+                    Ok(Value::Float(n))
+                } else {
+                    self.error(format!("invalid value `{}`", slice))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Parser, Value};
+
+    #[test]
+    fn parses_top_level_values() {
+        let src = "input = \"content\" # trailing\nthreads = 4\nratio = 1.5\nwatch = true";
+        let table = Parser::from_str(src).parse().unwrap();
+        assert_eq!(
+            table.get("input"),
+            Some(&Value::String("content".to_owned()))
+        );
+        assert_eq!(table.get("threads"), Some(&Value::Integer(4)));
+        assert_eq!(table.get("ratio"), Some(&Value::Float(1.5)));
+        assert_eq!(table.get("watch"), Some(&Value::Bool(true)));
+    }
+    #[test]
+    fn parses_nested_tables() {
+        let src = [
+            "[jobs]",
+            "helpers = false",
+            "",
+            "[collections.\"qwik city\"]",
+            "export = 'qwikCity'",
+        ]
+        .join("\n");
+        let table = Parser::from_str(&src).parse().unwrap();
+        let Some(Value::Table(jobs)) = table.get("jobs") else {
+            panic!("`jobs` is not a table");
+        };
+        assert_eq!(jobs.get("helpers"), Some(&Value::Bool(false)));
+        let Some(Value::Table(collections)) = table.get("collections") else {
+            panic!("`collections` is not a table");
+        };
+        let Some(Value::Table(collection)) = collections.get("qwik city") else {
+            panic!("`qwik city` is not a table");
+        };
+        assert_eq!(
+            collection.get("export"),
+            Some(&Value::String("qwikCity".to_owned()))
+        );
+    }
+    #[test]
+    fn parses_arrays_and_inline_tables() {
+        let src = "xs = [\n  \"a\",\n  \"b\", # comment\n]\nfield = { type = \"string\", required = true }";
+        let table = Parser::from_str(src).parse().unwrap();
+        let Some(Value::Array(xs)) = table.get("xs") else {
+            panic!("`xs` is not an array");
+        };
+        assert_eq!(xs.len(), 2);
+        let Some(Value::Table(field)) = table.get("field") else {
+            panic!("`field` is not a table");
+        };
+        assert_eq!(field.get("type"), Some(&Value::String("string".to_owned())));
+        assert_eq!(field.get("required"), Some(&Value::Bool(true)));
+    }
+    #[test]
+    fn reports_error_line() {
+        let src = "a = 1\nb = \"unterminated\nc = 2";
+        let err = Parser::from_str(src).parse().unwrap_err();
+        assert_eq!(err.line, 2);
+        let err = Parser::from_str("a = 1\na = 2").parse().unwrap_err();
+        assert_eq!(err.message, "duplicate key `a`");
+        let err = Parser::from_str("[jobs]\na = 1\n[jobs]\nb = 2")
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            (err.line, err.message.as_str()),
+            (3, "duplicate table `[jobs]`")
+        );
+        for value in ["inf", "-nan", "infinity"] {
+            let err = Parser::from_str(&format!("a = {}", value))
+                .parse()
+                .unwrap_err();
+            assert_eq!(err.message, format!("invalid value `{}`", value));
+        }
+    }
+}
@@ -3,23 +3,35 @@ use std::hash::Hasher;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use crate::config::Config;
 use crate::html_writer::ContentVec;
+use crate::navigation::{Link, Neighbours};
+use crate::schema::Literal;
 use crate::utils::get_content_ranges;
 use crate::yaml;
 use crate::yaml::{Yaml, YamlError};
 
-pub struct Config {
-    pub input: PathBuf,
-    pub output: PathBuf,
-    pub routes: PathBuf,
+/// The `_id` of the page at `path`, relative to the input directory.
+pub fn page_id(path: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(path.as_bytes());
+    hasher.finish()
 }
 
-impl Config {
-    pub fn new(input: PathBuf, output: PathBuf, routes: PathBuf) -> Self {
+/// What a page module says about the page beyond its own file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PageMeta {
+    pub draft: bool,
+    pub prev: Option<Link>,
+    pub next: Option<Link>,
+}
+
+impl PageMeta {
+    pub fn new(config: &Config, content: &Content, token: &Token, neighbours: Neighbours) -> Self {
         Self {
-            input,
-            output,
-            routes,
+            draft: crate::publish::is_draft(content, token),
+            prev: neighbours.prev.map(|idx| Link::new(content, config, idx)),
+            next: neighbours.next.map(|idx| Link::new(content, config, idx)),
         }
     }
 }
@@ -40,11 +52,11 @@ impl<'a> Page<'a> {
         frontmatter: &'a str,
         raw: &'a str,
         content: &ContentVec,
+        defaults: &[(&str, &Literal)],
+        meta: &PageMeta,
         w: &mut W,
     ) -> std::io::Result<()> {
-        let mut hasher = DefaultHasher::new();
-        hasher.write(p.as_ref().to_str().unwrap_or_default().as_bytes());
-        let _id = hasher.finish();
+        let _id = page_id(p.as_ref().to_str().unwrap_or_default());
         let _path = p.as_ref().to_path_buf();
         let _slug = _path.file_stem().and_then(|s| s.to_str());
         let _directory = _path.parent().and_then(|s| s.to_str());
@@ -58,8 +70,42 @@ impl<'a> Page<'a> {
             _directory.unwrap_or_default()
         ))?;
         w.write_fmt(format_args!("_content: {}, ", content))?;
+        w.write_fmt(format_args!("_draft: {}, ", meta.draft))?;
+        let stats = &content.stats;
+        w.write_fmt(format_args!(
+            "_wordCount: {}, _readingTime: {}, _characterCount: {}, ",
+            stats.words, stats.reading_time, stats.characters
+        ))?;
+        w.write_all(b"_excerpt: { html: ")?;
+        crate::json::write_str(w, &content.excerpt.html)?;
+        w.write_all(b", text: ")?;
+        crate::json::write_str(w, &content.excerpt.text)?;
+        w.write_all(b" }, _headings: ")?;
+        content.write_headings(w)?;
+        w.write_all(b", _toc: ")?;
+        content.write_toc(w)?;
+        for (name, link) in [("_prev", &meta.prev), ("_next", &meta.next)] {
+            w.write_fmt(format_args!(", {}: ", name))?;
+            match link {
+                Some(link) => link.write_js(w)?,
+                None => w.write_all(b"undefined")?,
+            }
+        }
+        w.write_all(b", ")?;
+        let mut first = true;
         if let Ok(yaml) = yaml {
             yaml.write_json(w)?;
+            first = yaml.is_empty();
+        }
+        // Schema defaults for fields the frontmatter leaves out.
+        for (name, value) in defaults {
+            if !first {
+                w.write_all(b", ")?;
+            }
+            first = false;
+            crate::json::write_str(w, name)?;
+            w.write_all(b": ")?;
+            value.write_js(w)?;
         }
         w.write_all(" }".as_bytes())?;
         Ok(())
@@ -80,7 +126,6 @@ pub struct Content {
 }
 
 impl Content {
-    #[allow(dead_code)]
     pub fn new() -> Self {
         Self::default()
     }
@@ -127,6 +172,17 @@ impl Content {
     pub fn frontmatter<'a>(&'a self, token: &'a Token) -> Result<Yaml<'a>, YamlError> {
         yaml::Parser::from_str(self.frontmatter_raw(token)).parse()
     }
+    pub fn find(&self, path: &str) -> Option<&Token> {
+        self.tokens.iter().find(|t| self.path(t) == path)
+    }
+    /// Where the frontmatter starts within [`Content::source`].
+    pub fn frontmatter_offset(&self, token: &Token) -> usize {
+        token.frontmatter.0 - token.path.1
+    }
+    /// The full file as read from disk, including frontmatter delimiters.
+    pub fn source(&self, token: &Token) -> &str {
+        &self.raw[token.path.1..token.body.1]
+    }
     pub fn body_raw(&self, token: &Token) -> &str {
         &self.raw[token.body.0..token.body.1]
     }
@@ -76,7 +76,7 @@ pub fn write_camel_case<W: Write>(string: &str, w: &mut W) -> std::io::Result<()
 pub fn write_snake_case<W: Write>(string: &str, w: &mut W) -> std::io::Result<()> {
     for char in string.chars() {
         if char.is_whitespace() || char.is_ascii_punctuation() {
-            let _ = w.write(&[b'_'])?;
+            let _ = w.write(b"_")?;
             continue;
         }
         let _ = w.write(&[char.to_ascii_lowercase() as u8])?;
@@ -92,7 +92,7 @@ pub fn output_path<P: AsRef<Path>>(outdir: P, path: &str) -> PathBuf {
     };
     let mut filename = path.trim_start_matches('/').replace('/', "_");
     filename.push_str(extension);
-    return outdir.as_ref().join(&filename);
+    outdir.as_ref().join(&filename)
 }
 #[inline]
 pub fn write_output_path<P: AsRef<Path>, W: Write>(
@@ -108,7 +108,7 @@ pub fn write_output_path<P: AsRef<Path>, W: Write>(
     w.write_fmt(format_args!("{}", outdir.as_ref().display()))?;
     for c in path.trim_start_matches('/').chars() {
         if c == '/' {
-            w.write_all(&[b'_'])?;
+            w.write_all(b"_")?;
             continue;
         }
         w.write_all(&[c as u8])?;
@@ -116,11 +116,60 @@ pub fn write_output_path<P: AsRef<Path>, W: Write>(
     Ok(())
     // w.write_all(extension.as_bytes())
 }
+/// 64-bit FNV-1a. Unlike `DefaultHasher` its output is stable across Rust
+/// releases, so it is safe to persist.
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl std::hash::Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+#[inline]
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = StableHasher::default();
+    std::hash::Hasher::write(&mut hasher, bytes);
+    std::hash::Hasher::finish(&hasher)
+}
+
+/// Lowercases `text` and joins its words with `-`, dropping punctuation.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for char in text.chars() {
+        if char.is_alphanumeric() || char == '_' {
+            slug.extend(char.to_lowercase());
+        } else if (char.is_whitespace() || char == '-') && !slug.is_empty() && !slug.ends_with('-')
+        {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        slug.push_str("section");
+    }
+    slug
+}
+
 #[inline]
 pub fn html_tag(string: &str) -> &str {
     let start = string.find(|c| c != '<').unwrap_or(0);
     let end = string[start..]
-        .find(|c| matches!(c, '/' | '>' | ' '))
+        .find(['/', '>', ' '])
         .map(|c| c + 1)
         .unwrap_or(string.len());
     string[start..end].trim()
@@ -130,7 +179,7 @@ pub fn html_tag(string: &str) -> &str {
 mod test {
     use std::{borrow::Cow, path::Path};
 
-    use super::{capitalize, get_content_ranges, html_tag, output_path};
+    use super::{capitalize, get_content_ranges, html_tag, output_path, stable_hash};
 
     #[test]
     fn gets_empty_file_ranges() {
@@ -205,4 +254,9 @@ mod test {
             assert_eq!(html_tag(tag), "tag")
         }
     }
+    #[test]
+    fn hashes_stably() {
+        assert_eq!(stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
 }
@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    cache::Cache,
+    config::Config,
+    diagnostics::Report,
+    pipeline,
+    threadpool::{Job, ThreadPool},
+    types::Content,
+};
+
+/// Polling keeps the watcher free of platform specific notification APIs.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification time and length of every file below a directory.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Snapshot {
+    files: HashMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl Snapshot {
+    fn scan(root: &Path, skip: &dyn Fn(&Path) -> bool) -> Self {
+        let mut snapshot = Self::default();
+        snapshot.scan_rec(root, skip);
+        snapshot
+    }
+    fn scan_rec(&mut self, curr: &Path, skip: &dyn Fn(&Path) -> bool) {
+        if let Ok(dir) = std::fs::read_dir(curr) {
+            for entry in dir.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if skip(&path) {
+                    continue;
+                }
+                match entry.metadata() {
+                    Ok(meta) if meta.is_dir() => self.scan_rec(&path, skip),
+                    Ok(meta) => {
+                        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                        self.files.insert(path, (modified, meta.len()));
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+    /// Files that are new or modified in `newer`, and files missing from it.
+    fn diff(&self, newer: &Snapshot) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let modified = newer
+            .files
+            .iter()
+            .filter(|(path, stamp)| self.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let removed = self
+            .files
+            .keys()
+            .filter(|path| !newer.files.contains_key(*path))
+            .cloned()
+            .collect();
+        (modified, removed)
+    }
+}
+
+pub struct Watcher {
+    config: Arc<Config>,
+    content: Snapshot,
+    routes: Snapshot,
+    frontmatter: HashMap<String, u64>,
+}
+
+impl Watcher {
+    pub fn new(config: Arc<Config>) -> Self {
+        Self {
+            config,
+            content: Snapshot::default(),
+            routes: Snapshot::default(),
+            frontmatter: HashMap::new(),
+        }
+    }
+    fn scan_content(&self) -> Snapshot {
+        let output = &self.config.output;
+        Snapshot::scan(&self.config.input, &|p| p.starts_with(output))
+    }
+    fn scan_routes(&self) -> Snapshot {
+        // Skip the files we generate ourselves, otherwise every rebuild
+        // would trigger another one.
+        let generated = self.config.naming.route_params.as_str();
+        Snapshot::scan(&self.config.routes, &|p| {
+            p.file_name().map(|n| n == generated) == Some(true)
+        })
+    }
+    /// Runs a full build, then rebuilds on every change until the process is killed.
+    pub fn run(mut self) -> Result<(), String> {
+        pipeline::prepare_output(&self.config)?;
+        self.content = self.scan_content();
+        self.routes = self.scan_routes();
+        let content = Arc::new(pipeline::load_content(&self.config));
+        self.frontmatter = pipeline::frontmatter_hashes(&content);
+        let cache = Arc::new(Cache::new(&self.config, &content, false));
+        let pool = ThreadPool::new(self.config.threads);
+        pipeline::execute_all(&pool, content.clone(), self.config.clone(), cache.clone());
+        println!("{} content files", content.len());
+        let report = pool.finish();
+        print_elapsed(&report);
+        report.print(&content);
+        save_cache(&cache, &report);
+        println!(
+            "Watching {} and {}",
+            self.config.input.display(),
+            self.config.routes.display()
+        );
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            self.poll();
+        }
+    }
+    /// Re-runs only the jobs affected by changes since the last poll.
+    fn poll(&mut self) {
+        let content_snapshot = self.scan_content();
+        let routes_snapshot = self.scan_routes();
+        let (modified, removed) = self.content.diff(&content_snapshot);
+        let routes_changed = self.routes != routes_snapshot;
+        if modified.is_empty() && removed.is_empty() && !routes_changed {
+            return;
+        }
+        self.content = content_snapshot;
+        self.routes = routes_snapshot;
+
+        let config = self.config.clone();
+        let pool = ThreadPool::new(config.threads);
+        let mut cache = None;
+        let mut content = Arc::new(Content::new());
+        if !modified.is_empty() || !removed.is_empty() {
+            content = Arc::new(pipeline::load_content(&config));
+            for path in removed.iter() {
+                println!("Removed {}", path.display());
+            }
+            for path in modified.iter() {
+                println!("Changed {}", path.display());
+            }
+            // Deleted files, and edited ones that are no longer published.
+            let before = self.frontmatter.keys().map(String::as_str);
+            if let Err(e) = pipeline::remove_dropped_outputs(&config, before, &content) {
+                eprintln!("Remove {}: {}", config.files_dir().display(), e);
+            }
+            cache = Some(pipeline::execute_changed(
+                &pool,
+                content.clone(),
+                config.clone(),
+                &modified,
+                &mut self.frontmatter,
+            ));
+        }
+        if routes_changed && config.jobs.route_params && config.routes.is_dir() {
+            println!("Routes changed");
+            pool.execute(Job::GenerateRouteParams(config.clone()));
+        }
+        // Wait for the jobs before recording what they produced.
+        let report = pool.finish();
+        print_elapsed(&report);
+        report.print(&content);
+        if let Some(cache) = cache {
+            save_cache(&cache, &report);
+        }
+    }
+}
+
+fn save_cache(cache: &Cache, report: &Report) {
+    if let Err(e) = cache.finish(report) {
+        eprintln!("Cache {}", e);
+    }
+}
+
+fn print_elapsed(report: &Report) {
+    println!(
+        "Finished in {:.3}ms",
+        report.elapsed.as_nanos() as f32 / 1000000.0
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::{path::PathBuf, time::SystemTime};
+
+    use super::Snapshot;
+
+    #[test]
+    fn diffs_snapshots() {
+        let stamp = |secs| {
+            (
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                10,
+            )
+        };
+        let mut old = Snapshot::default();
+        old.files.insert(PathBuf::from("a.md"), stamp(1));
+        old.files.insert(PathBuf::from("b.md"), stamp(1));
+        let mut new = Snapshot::default();
+        new.files.insert(PathBuf::from("a.md"), stamp(2));
+        new.files.insert(PathBuf::from("c.md"), stamp(1));
+        let (mut modified, removed) = old.diff(&new);
+        modified.sort();
+        assert_eq!(modified, vec![PathBuf::from("a.md"), PathBuf::from("c.md")]);
+        assert_eq!(removed, vec![PathBuf::from("b.md")]);
+    }
+}
@@ -14,6 +14,8 @@ pub enum YamlKind {
     Bool,
     Number,
     Null,
+    // An unquoted ISO 8601 date or timestamp
+    Date,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -38,7 +40,8 @@ pub struct Yaml<'a> {
     src: &'a str,
     inner: Vec<YamlNode>,
     tags: Option<usize>,
-    draft: Option<usize>,
+    /// Leading whitespace trimmed from the source, see [`YamlError::span`].
+    offset: usize,
 }
 
 #[allow(dead_code)]
@@ -49,23 +52,82 @@ impl<'a> Yaml<'a> {
     pub fn len(&self) -> usize {
         self.inner.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+    /// The trimmed source node ranges point into.
+    pub fn src(&self) -> &'a str {
+        self.src
+    }
+    /// The value of a top-level key.
+    pub fn get(&self, key: &str) -> Option<&YamlNode> {
+        let idx = self.inner.iter().position(|node| {
+            node.kind == YamlKind::Key
+                && node.parent == 0
+                && node
+                    .slice(self.src)
+                    .trim_start_matches(['"', '\''])
+                    .trim_end_matches(['"', '\''])
+                    == key
+        })?;
+        // Values directly follow their key, whose id is its index plus one.
+        self.inner.get(idx + 1).filter(|n| n.parent == idx + 1)
+    }
+    /// The unquoted value of a top-level key holding a string.
+    pub fn string(&self, key: &str) -> Option<&'a str> {
+        let node = self.get(key).filter(|n| n.kind == YamlKind::String)?;
+        Some(
+            node.slice(self.src)
+                .trim_start_matches(['"', '\''])
+                .trim_end_matches(['"', '\'']),
+        )
+    }
     pub fn write_json<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
         write_json_values_rec(&self.inner, self.src, w)?;
         Ok(())
     }
-    pub fn is_draft(&self) -> bool {
-        if let Some(node) = self.draft.and_then(|idx| self.inner.get(idx)) {
-            if node.kind == YamlKind::Bool {
-                match node.slice(self.src) {
-                    "true" => return true,
-                    "false" => return false,
-                    _ => todo!("Non true/false bool value"),
-                }
-            } else {
-                println!("Draft must be a boolean")
-            }
+    /// Whether the top-level `draft` key is true. Missing and null mean false.
+    pub fn is_draft(&self) -> Result<bool, YamlError> {
+        let Some(node) = self.get("draft") else {
+            return Ok(false);
+        };
+        match (node.kind, node.slice(self.src)) {
+            (YamlKind::Bool, "true" | "YES") => Ok(true),
+            (YamlKind::Bool, "false" | "NO") | (YamlKind::Null, _) => Ok(false),
+            _ => Err(self.error_at(node, YamlErrorKind::ExpectedBoolean)),
+        }
+    }
+    /// The date held by a top-level key. Quoted dates are strings but are
+    /// read all the same. Missing and null mean no date.
+    pub fn date(&self, key: &str) -> Result<Option<crate::date::Timestamp>, YamlError> {
+        let Some(node) = self.get(key) else {
+            return Ok(None);
+        };
+        if node.kind == YamlKind::Null {
+            return Ok(None);
+        }
+        match node.kind {
+            YamlKind::Date | YamlKind::String => node_date(node, self.src)
+                .map(Some)
+                .ok_or_else(|| self.error_at(node, YamlErrorKind::ExpectedDate)),
+            _ => Err(self.error_at(node, YamlErrorKind::ExpectedDate)),
+        }
+    }
+    /// Whether any value is a date, which modules write as `ISODate`.
+    pub fn has_dates(&self) -> bool {
+        self.inner.iter().any(|node| node.kind == YamlKind::Date)
+    }
+    /// An error covering `node`, with a span like the parser's errors.
+    pub fn error_at(&self, node: &YamlNode, kind: YamlErrorKind) -> YamlError {
+        let range = node.range();
+        YamlError {
+            kind,
+            span: range.start + self.offset..range.end + self.offset,
         }
-        false
+    }
+    /// The value of the `tags` key, if there is one.
+    pub fn tags_node(&self) -> Option<&YamlNode> {
+        self.tags.and_then(|idx| self.inner.get(idx))
     }
     pub fn get_tags(&'a self) -> Tags<'a> {
         if let Some((idx, node)) = self
@@ -93,20 +155,56 @@ pub struct Parser<'a> {
     curr: usize,
     nodes: Vec<YamlNode>,
     tags: Option<usize>,
-    draft: Option<usize>,
+    /// Leading whitespace trimmed from the source, added back to error spans.
+    offset: usize,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub enum YamlError {
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum YamlErrorKind {
     UnexpectedEndOfInput,
     EmptyString,
     ExpectedDigit,
     Expected(char),
+    Unexpected(char),
+    ExpectedBoolean,
+    ExpectedDate,
+    InvalidDate,
+}
+
+/// A parse error. `span` is a byte range into the source given to
+/// [`Parser::from_str`], before trimming.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct YamlError {
+    pub kind: YamlErrorKind,
+    pub span: Range<usize>,
+}
+
+impl std::fmt::Display for YamlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            YamlErrorKind::UnexpectedEndOfInput => write!(f, "unexpected end of input"),
+            YamlErrorKind::EmptyString => write!(f, "expected a value"),
+            YamlErrorKind::ExpectedDigit => write!(f, "expected a digit"),
+            YamlErrorKind::Expected(c) => write!(f, "expected `{}`", c),
+            YamlErrorKind::Unexpected(c) => write!(f, "unexpected `{}`", c),
+            YamlErrorKind::ExpectedBoolean => write!(f, "expected `true` or `false`"),
+            YamlErrorKind::ExpectedDate => {
+                write!(
+                    f,
+                    "expected a date like `2022-08-22` or `2022-08-22T10:00:00Z`"
+                )
+            }
+            YamlErrorKind::InvalidDate => write!(f, "not a valid date"),
+        }
+    }
 }
 
 impl<'a> Parser<'a> {
+    // `FromStr` cannot borrow from its input, which the parser relies on.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(src: &'a str) -> Self {
         let trimmed = src.trim();
+        let offset = src.len() - src.trim_start().len();
         Self {
             start: 0,
             curr: 0,
@@ -114,7 +212,23 @@ impl<'a> Parser<'a> {
             src: trimmed,
             nodes: Vec::default(),
             tags: None,
-            draft: None,
+            offset,
+        }
+    }
+    /// An error covering the uncommitted input.
+    fn error(&self, kind: YamlErrorKind) -> YamlError {
+        YamlError {
+            kind,
+            span: self.start + self.offset..self.curr + self.offset,
+        }
+    }
+    /// An error covering the next character, or the end of input.
+    fn error_at_next(&mut self, kind: YamlErrorKind) -> YamlError {
+        let len = self.peek().map(char::len_utf8).unwrap_or(0);
+        let start = self.curr + self.offset;
+        YamlError {
+            kind,
+            span: start..start + len,
         }
     }
     pub fn slice(&self) -> &str {
@@ -163,10 +277,8 @@ impl<'a> Parser<'a> {
             range: (self.start, self.curr),
         });
         let id = self.nodes.len();
-        match self.slice() {
-            "tags" => self.tags = Some(id),
-            "draft" => self.draft = Some(id),
-            _ => {}
+        if self.slice() == "tags" {
+            self.tags = Some(id);
         }
         id
     }
@@ -176,7 +288,7 @@ impl<'a> Parser<'a> {
             src: self.src,
             inner: self.nodes,
             tags: self.tags,
-            draft: self.draft,
+            offset: self.offset,
         })
     }
     pub fn parse_object(&mut self, parent: usize) -> Result<(), YamlError> {
@@ -213,7 +325,7 @@ impl<'a> Parser<'a> {
                     self.skip_ws();
                     self.commit();
                 }
-                Some(c) => todo!("Unhandled {:?} in inline list", c),
+                Some(c) => return Err(self.error_at_next(YamlErrorKind::Unexpected(c))),
             }
         }
         self.commit();
@@ -241,7 +353,9 @@ impl<'a> Parser<'a> {
                 }
             }
         }
-        assert_eq!(Some('}'), self.peek());
+        if self.peek() != Some('}') {
+            return Err(self.error_at_next(YamlErrorKind::Expected('}')));
+        }
         self.chomp();
         self.commit();
         Ok(())
@@ -293,7 +407,7 @@ impl<'a> Parser<'a> {
             if next == ':' {
                 let key = self.slice();
                 if key.is_empty() {
-                    return Err(YamlError::EmptyString);
+                    return Err(self.error_at_next(YamlErrorKind::EmptyString));
                 }
                 let parent_idx = self.push_key(parent);
                 self.chomp(); // Skip colon
@@ -303,7 +417,7 @@ impl<'a> Parser<'a> {
             }
             self.chomp()
         }
-        Err(YamlError::Expected(':'))
+        Err(self.error(YamlErrorKind::Expected(':')))
     }
     fn parse_multiline_object(&mut self, parent: usize, indent: usize) -> Result<(), YamlError> {
         self.commit();
@@ -334,7 +448,7 @@ impl<'a> Parser<'a> {
     }
     fn parse_value(&mut self, parent: usize) -> Result<(), YamlError> {
         match self.peek() {
-            None => Err(YamlError::UnexpectedEndOfInput),
+            None => Err(self.error_at_next(YamlErrorKind::UnexpectedEndOfInput)),
             Some('[') => self.parse_inline_list(parent),
             Some('{') => self.parse_inline_object(parent),
             Some('\r') | Some('\n') => {
@@ -361,7 +475,7 @@ impl<'a> Parser<'a> {
             next = self.peek();
         }
         if next.map(|c| !c.is_ascii_digit()).unwrap_or(false) {
-            return Err(YamlError::ExpectedDigit);
+            return Err(self.error_at_next(YamlErrorKind::ExpectedDigit));
         }
         let mut is_decimal = false;
         loop {
@@ -374,15 +488,16 @@ impl<'a> Parser<'a> {
                 Some(char) => {
                     if char == '.' {
                         if is_decimal {
-                            return self.parse_string_bool_null(parent);
+                            return self.parse_scalar(parent);
                         } else {
                             is_decimal = true;
                             self.chomp();
                             continue;
                         }
                     }
+                    // Dates and the like, which start out like numbers.
                     if !char.is_ascii_digit() {
-                        return self.parse_string_bool_null(parent);
+                        return self.parse_scalar(parent);
                     }
                     self.chomp();
                 }
@@ -394,7 +509,7 @@ impl<'a> Parser<'a> {
         let quote = match self.peek() {
             Some('\'') => '\'',
             Some('"') => '"',
-            _ => return Err(YamlError::Expected('"')),
+            _ => return Err(self.error_at_next(YamlErrorKind::Expected('"'))),
         };
         self.chomp();
         loop {
@@ -408,20 +523,32 @@ impl<'a> Parser<'a> {
                     }
                     self.chomp()
                 }
-                None => return Err(YamlError::Expected(quote)),
+                None => return Err(self.error(YamlErrorKind::Expected(quote))),
             }
         }
         Ok(())
     }
     fn parse_string_bool_null(&mut self, parent: usize) -> Result<(), YamlError> {
         self.commit();
+        self.parse_scalar(parent)
+    }
+    /// Reads the rest of an unquoted value started at `self.start`.
+    fn parse_scalar(&mut self, parent: usize) -> Result<(), YamlError> {
         loop {
             match self.peek() {
                 Some(',') | Some('\r') | Some('\n') | Some(']') | Some('}') | None => {
                     let kind = match self.slice().trim_end() {
-                        "" => return Err(YamlError::EmptyString),
+                        "" => return Err(self.error_at_next(YamlErrorKind::EmptyString)),
                         "false" | "NO" | "true" | "YES" => YamlKind::Bool,
                         "NULL" => YamlKind::Null,
+                        value if crate::date::parse(value).is_some() => YamlKind::Date,
+                        value if looks_like_date(value) => {
+                            let end = self.start + value.len();
+                            return Err(YamlError {
+                                kind: YamlErrorKind::InvalidDate,
+                                span: self.start + self.offset..end + self.offset,
+                            });
+                        }
                         _ => YamlKind::String,
                     };
                     let mut real_curr = self.curr;
@@ -479,19 +606,62 @@ impl<'a> Iterator for Tags<'a> {
             }
             let slice = next
                 .slice(self.src)
-                .trim_start_matches(|c| c == '\'' || c == '"')
-                .trim_end_matches(|c| c == '\'' || c == '"');
-            let is_valid = slice.starts_with(|c: char| c.is_ascii_alphabetic())
-                && slice
-                    .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '-' | ' ' | '_'))
-                    .is_none();
-            if is_valid && next.kind == YamlKind::String {
+                .trim_start_matches(['\'', '"'])
+                .trim_end_matches(['\'', '"']);
+            if is_valid_tag(slice) && next.kind == YamlKind::String {
                 return Some(slice);
             }
         }
     }
 }
 
+/// Tags become TypeScript identifiers, so only a conservative set of
+/// characters is accepted.
+pub fn is_valid_tag(tag: &str) -> bool {
+    tag.starts_with(|c: char| c.is_ascii_alphabetic())
+        && tag
+            .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '-' | ' ' | '_'))
+            .is_none()
+}
+
+/// Whether an unquoted value is shaped like `2022-08-22`, optionally followed
+/// by a time like `T10:00:00Z`, so failing to parse it as a date is a mistake
+/// rather than a string. Text after the date, as in `2022-08-22-my-post`,
+/// makes it a string.
+fn looks_like_date(value: &str) -> bool {
+    let shaped = |bytes: &[u8], pattern: &[u8]| {
+        bytes.len() == pattern.len()
+            && bytes.iter().zip(pattern).all(|(b, p)| match p {
+                b'0' => b.is_ascii_digit(),
+                p => b == p,
+            })
+    };
+    let bytes = value.as_bytes();
+    if bytes.len() < 10 || !shaped(&bytes[..10], b"0000-00-00") {
+        return false;
+    }
+    match &bytes[10..] {
+        [] => true,
+        [b'T' | b't' | b' ', time @ ..] => {
+            time.len() >= 5
+                && shaped(&time[..5], b"00:00")
+                && time[5..].iter().all(|b| {
+                    b.is_ascii_digit() || matches!(b, b':' | b'.' | b'+' | b'-' | b'Z' | b'z')
+                })
+        }
+        _ => false,
+    }
+}
+
+/// The date held by a [`YamlKind::Date`] node, or a quoted string.
+pub fn node_date(node: &YamlNode, src: &str) -> Option<crate::date::Timestamp> {
+    crate::date::parse(
+        node.slice(src)
+            .trim_start_matches(['"', '\''])
+            .trim_end_matches(['"', '\'']),
+    )
+}
+
 #[inline]
 pub fn write_json_values_rec<W: Write>(
     nodes: &[YamlNode],
@@ -521,8 +691,8 @@ pub fn write_json_values_rec<W: Write>(
             YamlKind::Key => {
                 let slice = node
                     .slice(src)
-                    .trim_start_matches(|c| c == '"' || c == '\'')
-                    .trim_end_matches(|c| c == '"' || c == '\'');
+                    .trim_start_matches(['"', '\''])
+                    .trim_end_matches(['"', '\'']);
                 w.write_fmt(format_args!("\"{}\": ", slice))?;
                 if id < nodes.len() {
                     let end = nodes[idx + 1..]
@@ -554,13 +724,21 @@ pub fn write_json_values_rec<W: Write>(
             YamlKind::String => {
                 let slice = node
                     .slice(src)
-                    .trim_start_matches(|c| c == '"' || c == '\'')
-                    .trim_end_matches(|c| c == '"' || c == '\'');
+                    .trim_start_matches(['"', '\''])
+                    .trim_end_matches(['"', '\'']);
                 w.write_fmt(format_args!("\"{}\"", slice))?;
             }
             YamlKind::Bool => w.write_all(node.slice(src).as_bytes())?,
             YamlKind::Number => w.write_all(node.slice(src).as_bytes())?,
             YamlKind::Null => w.write_all("undefined".as_bytes())?,
+            YamlKind::Date => match node_date(node, src) {
+                Some(date) => {
+                    w.write_all(b"\"")?;
+                    crate::date::write_iso(w, date)?;
+                    w.write_all(b"\" as ISODate")?;
+                }
+                None => w.write_all(b"undefined")?,
+            },
         }
         processed += 1;
         if processed < nodes.len() {
@@ -571,9 +749,10 @@ pub fn write_json_values_rec<W: Write>(
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
 
-    use super::{write_json_values_rec, Parser, Yaml, YamlKind};
+    use super::{write_json_values_rec, Parser, Yaml, YamlErrorKind, YamlKind};
 
     fn expect_nodes(src: &str, yaml: Yaml, expected: Vec<(&str, YamlKind, usize)>) {
         assert_eq!(yaml.len(), expected.len());
@@ -773,10 +952,79 @@ mod tests {
         println!("{}", String::from_utf8(out).unwrap())
     }
     #[test]
+    fn it_reports_error_spans() {
+        let src = "\ntitle: x\ntags: [\"a\" b]";
+        let error = Parser::from_str(src).parse().unwrap_err();
+        assert_eq!(error.kind, YamlErrorKind::Unexpected('b'));
+        assert_eq!(&src[error.span], "b");
+        let error = Parser::from_str("key: { a: 1").parse().unwrap_err();
+        assert_eq!(error.kind, YamlErrorKind::Expected('}'));
+    }
+    #[test]
     fn it_can_be_draft() {
         let src = "draft: true";
         let yaml = Parser::from_str(src).parse().unwrap();
-        assert!(yaml.is_draft())
+        assert!(yaml.is_draft().unwrap());
+        let src = "\ntitle: x\ndraft: maybe";
+        let error = Parser::from_str(src)
+            .parse()
+            .unwrap()
+            .is_draft()
+            .unwrap_err();
+        assert_eq!(error.kind, YamlErrorKind::ExpectedBoolean);
+        assert_eq!(&src[error.span], "maybe");
+    }
+    #[test]
+    fn it_reads_dates() {
+        let src = "publishDate: \"2022-08-22\"\nexpiryDate: NULL\nupdated: soon";
+        let yaml = Parser::from_str(src).parse().unwrap();
+        assert_eq!(yaml.date("publishDate"), Ok(Some(1661126400)));
+        assert_eq!(yaml.date("expiryDate"), Ok(None));
+        assert_eq!(yaml.date("missing"), Ok(None));
+        let error = yaml.date("updated").unwrap_err();
+        assert_eq!(error.kind, YamlErrorKind::ExpectedDate);
+        assert_eq!(&src[error.span], "soon");
+    }
+    #[test]
+    fn it_parses_dates() {
+        let src = "a: 2022-08-22\nb: \"2022-08-22 12:00+02:00\"\nc: 1.2.3\nd: 2022-08-22T10:00Z";
+        let yaml = Parser::from_str(src).parse().unwrap();
+        let expected = vec![
+            ("a", YamlKind::Key, 0),
+            ("2022-08-22", YamlKind::Date, 1),
+            ("b", YamlKind::Key, 0),
+            ("\"2022-08-22 12:00+02:00\"", YamlKind::String, 3),
+            ("c", YamlKind::Key, 0),
+            ("1.2.3", YamlKind::String, 5),
+            ("d", YamlKind::Key, 0),
+            ("2022-08-22T10:00Z", YamlKind::Date, 7),
+        ];
+        let mut out = Vec::new();
+        yaml.write_json(&mut out).unwrap();
+        assert_eq!(yaml.date("b"), Ok(Some(1661162400)));
+        expect_nodes(src, yaml, expected);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\"a\": \"2022-08-22T00:00:00.000Z\" as ISODate, \"b\": \"2022-08-22 12:00+02:00\", \"c\": \"1.2.3\", \"d\": \"2022-08-22T10:00:00.000Z\" as ISODate"
+        );
+    }
+    #[test]
+    fn it_rejects_invalid_dates() {
+        for (src, date) in [
+            ("d: 2022-08-32", "2022-08-32"),
+            ("d: 2022-13-01  ", "2022-13-01"),
+            ("d: 2022-08-22T25:00Z", "2022-08-22T25:00Z"),
+            ("list: [2022-02-30]", "2022-02-30"),
+        ] {
+            let error = Parser::from_str(src).parse().unwrap_err();
+            assert_eq!(error.kind, YamlErrorKind::InvalidDate);
+            assert_eq!(&src[error.span], date);
+        }
+        let src = "d: \"2022-08-32\"\nslug: 2022-08-22-my-post\ntitle: 2022-08-22 Release notes";
+        let yaml = Parser::from_str(src).parse().unwrap();
+        assert_eq!(yaml.get("d").unwrap().kind, YamlKind::String);
+        assert_eq!(yaml.string("slug"), Some("2022-08-22-my-post"));
+        assert_eq!(yaml.string("title"), Some("2022-08-22 Release notes"));
     }
     #[test]
     fn it_can_have_tags() {
@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+use qwik_city_content::{build, Config};
+
+#[test]
+fn builds_a_content_directory() {
+    let root = std::env::temp_dir().join(format!("qcc-build-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let content = root.join("content");
+    std::fs::create_dir_all(content.join("posts")).unwrap();
+    std::fs::write(
+        content.join("posts/hello.md"),
+        "---\ntitle: Hello\ntags: [news]\n---\n# Hello",
+    )
+    .unwrap();
+    std::fs::write(content.join("posts/broken.md"), "---\ntitle: \"open\n---\n").unwrap();
+    let mut config = Config::new(content, root.join("generated"), PathBuf::from("no-routes"));
+    config.threads = 1;
+
+    let result = build(config).unwrap();
+    assert_eq!(result.content.len(), 2);
+    assert_eq!(result.report.errors(), 1);
+    assert!(result.failed(false));
+    let module = root.join("generated/files/posts_hello.md.ts");
+    assert!(std::fs::read_to_string(module)
+        .unwrap()
+        .contains(r#"<h1 id=\"hello\">Hello</h1>"#));
+    let collections = std::fs::read_to_string(root.join("generated/collections.ts")).unwrap();
+    assert!(collections.contains("export const news"));
+    std::fs::remove_dir_all(&root).unwrap();
+}